@@ -0,0 +1,446 @@
+use crate::ast;
+use crate::parser::{Section, Statement};
+use crate::target::Target;
+
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+type I = ast::ITypeOp;
+type J = ast::JTypeOp;
+type R = ast::RTypeOp;
+type V = ast::VTypeOp;
+type VM = ast::VMemTypeOp;
+
+/// The images of an assembled program
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Sections {
+    /// The `.text` section as big endian words, the last one padded with zero bytes
+    pub text: Vec<u32>,
+    /// The `.data` section
+    pub data: Vec<u8>,
+}
+
+/// How much of IMEM or DMEM a section takes up, in bytes
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Usage {
+    /// Bytes from the base address to the end of the section
+    pub used: u32,
+    /// Bytes left between the end of the section and the end of the memory
+    pub free: u32,
+}
+
+pub fn assemble(statements: &[Statement]) -> Sections {
+    let (mut text, mut data) = (vec![], vec![]);
+    for statement in statements {
+        let bytes = match statement.section {
+            Section::Text => &mut text,
+            Section::Data => &mut data,
+        };
+        for inst in &statement.insts {
+            bytes.extend_from_slice(&encode(inst).to_be_bytes());
+        }
+        bytes.extend_from_slice(&statement.data);
+    }
+    let text = text
+        .chunks(4)
+        .map(|x| {
+            let mut word = [0; 4];
+            word[..x.len()].copy_from_slice(x);
+            u32::from_be_bytes(word)
+        })
+        .collect();
+    Sections { text, data }
+}
+
+/// Formats the address, machine word and instruction of every assembled word.
+///
+/// Pseudoinstructions are listed as written followed by their indented expansion,
+/// data is listed four bytes a line next to its directive. Statements are listed
+/// in source order with a `.text` or `.data` line where the section changes.
+pub fn listing(statements: &[Statement], base: u32, cpu: Target) -> Vec<String> {
+    let mut names = BTreeMap::new();
+    for statement in statements {
+        if let (Section::Text, Some(label)) = (statement.section, statement.labels.first()) {
+            names.insert(statement.addr, label.to_string());
+        }
+    }
+
+    let mut lines = vec![];
+    let mut section = Section::Text;
+    for statement in statements {
+        if statement.section != section {
+            section = statement.section;
+            lines.push(format!("{:20}{}", "", section));
+        }
+        for label in &statement.labels {
+            lines.push(format!("{}:", label));
+        }
+        for (index, chunk) in statement.data.chunks(4).enumerate() {
+            let bytes: String = chunk.iter().map(|x| format!("{:02x}", x)).collect();
+            let addr = statement.addr.wrapping_add(4 * index as u32);
+            let line = match index {
+                0 => format!(
+                    "{:08x}  {:8}  {:7} {}",
+                    addr, bytes, statement.mnemonic, statement.ops
+                ),
+                _ => format!("{:08x}  {}", addr, bytes),
+            };
+            lines.push(line.trim_end().to_string());
+        }
+        // `.align` pads code with `nop`s
+        let directive = statement.mnemonic.starts_with('.') && !statement.insts.is_empty();
+        let indent = if statement.is_pseudo() || directive {
+            let line = format!("{:20}{:7} {}", "", statement.mnemonic, statement.ops);
+            lines.push(line.trim_end().to_string());
+            4
+        } else {
+            0
+        };
+        for (index, inst) in statement.insts.iter().enumerate() {
+            let pc = statement.addr.wrapping_add(4 * index as u32);
+            lines.push(format!(
+                "{:08x}  {:08x}  {:indent$}{}",
+                pc,
+                encode(inst),
+                "",
+                inst.at(pc, base, cpu, &names)
+            ));
+        }
+    }
+    lines
+}
+
+pub fn encode(inst: &ast::Instruction) -> u32 {
+    match inst {
+        ast::Instruction::Immediate {
+            op,
+            rs,
+            rt,
+            imm: ast::Immediate(imm),
+        } => encode_immediate(*op, rs.as_num(), rt.as_num(), *imm as u32),
+        ast::Instruction::Jump {
+            op,
+            target: ast::Target(target),
+        } => {
+            let opcode = match op {
+                J::J => 2,
+                J::Jal => 3,
+            };
+            opcode << 26 | ((target >> 2) & 0x3FFFFFF)
+        }
+        ast::Instruction::Register { op, rs, rt, rd, sa } => {
+            encode_register(*op, rs.as_num(), rt.as_num(), rd.as_num(), *sa)
+        }
+        ast::Instruction::Vector {
+            op,
+            vd,
+            vs,
+            vt,
+            de,
+            e,
+        } => encode_vector(*op, *vd as u32, *vs as u32, *vt as u32, *de, *e),
+        ast::Instruction::VectorMemory {
+            op,
+            vt,
+            e,
+            offset,
+            base,
+        } => encode_vector_memory(*op, *vt as u32, *e, *offset, base.as_num()),
+    }
+}
+
+#[rustfmt::skip]
+fn encode_immediate(op: ast::ITypeOp, rs: u32, rt: u32, imm: u32) -> u32 {
+    let (opcode, rs, rt) = match op {
+        I::Bltz => (1, rs, 0),
+        I::Bgez => (1, rs, 1),
+        I::Bltzl => (1, rs, 2),
+        I::Bgezl => (1, rs, 3),
+        I::Tgei => (1, rs, 8),
+        I::Tgeiu => (1, rs, 9),
+        I::Tlti => (1, rs, 10),
+        I::Tltiu => (1, rs, 11),
+        I::Teqi => (1, rs, 12),
+        I::Tnei => (1, rs, 14),
+        I::Bltzal => (1, rs, 16),
+        I::Bgezal => (1, rs, 17),
+        I::Bltzall => (1, rs, 18),
+        I::Bgezall => (1, rs, 19),
+        I::Beq => (4, rs, rt),
+        I::Bne => (5, rs, rt),
+        I::Blez => (6, rs, 0),
+        I::Bgtz => (7, rs, 0),
+        I::Addi => (8, rs, rt),
+        I::Addiu => (9, rs, rt),
+        I::Slti => (10, rs, rt),
+        I::Sltiu => (11, rs, rt),
+        I::Andi => (12, rs, rt),
+        I::Ori => (13, rs, rt),
+        I::Xori => (14, rs, rt),
+        I::Lui => (15, 0, rt),
+        I::Bc0f => (16, 8, 0),
+        I::Bc0t => (16, 8, 1),
+        I::Bc0fl => (16, 8, 2),
+        I::Bc0tl => (16, 8, 3),
+        I::Bc1f => (17, 8, 0),
+        I::Bc1t => (17, 8, 1),
+        I::Bc1fl => (17, 8, 2),
+        I::Bc1tl => (17, 8, 3),
+        I::Beql => (20, rs, rt),
+        I::Bnel => (21, rs, rt),
+        I::Blezl => (22, rs, 0),
+        I::Bgtzl => (23, rs, 0),
+        I::Daddi => (24, rs, rt),
+        I::Daddiu => (25, rs, rt),
+        I::Ldl => (26, rs, rt),
+        I::Ldr => (27, rs, rt),
+        I::Lb => (32, rs, rt),
+        I::Lh => (33, rs, rt),
+        I::Lwl => (34, rs, rt),
+        I::Lw => (35, rs, rt),
+        I::Lbu => (36, rs, rt),
+        I::Lhu => (37, rs, rt),
+        I::Lwr => (38, rs, rt),
+        I::Lwu => (39, rs, rt),
+        I::Sb => (40, rs, rt),
+        I::Sh => (41, rs, rt),
+        I::Swl => (42, rs, rt),
+        I::Sw => (43, rs, rt),
+        I::Sdl => (44, rs, rt),
+        I::Sdr => (45, rs, rt),
+        I::Swr => (46, rs, rt),
+        I::Cache => (47, rs, rt),
+        I::Ll => (48, rs, rt),
+        I::Lwc1 => (49, rs, rt),
+        I::Lld => (52, rs, rt),
+        I::Ldc1 => (53, rs, rt),
+        I::Ld => (55, rs, rt),
+        I::Sc => (56, rs, rt),
+        I::Swc1 => (57, rs, rt),
+        I::Scd => (60, rs, rt),
+        I::Sdc1 => (61, rs, rt),
+        I::Sd => (63, rs, rt),
+        e => panic!("Pseudoinstruction {:?} can not be encoded", e),
+    };
+    opcode << 26 | rs << 21 | rt << 16 | (imm & 0xFFFF)
+}
+
+#[rustfmt::skip]
+fn encode_register(op: ast::RTypeOp, rs: u32, rt: u32, rd: u32, sa: u32) -> u32 {
+    // Floating point instructions keep fs in `rs` and fd in `rd`
+    let float = |fmt: u32, funct: u32| 17 << 26 | fmt << 21 | rt << 16 | rs << 11 | rd << 6 | funct;
+    let special = |funct: u32| rs << 21 | rt << 16 | rd << 11 | (sa & 0x1F) << 6 | funct;
+    let cop = |opcode: u32, sub: u32| opcode << 26 | sub << 21 | rt << 16 | rd << 11;
+
+    match op {
+        R::Sll => special(0),
+        R::Srl => special(2),
+        R::Sra => special(3),
+        R::Sllv => special(4),
+        R::Srlv => special(6),
+        R::Srav => special(7),
+        R::Jr => special(8),
+        R::Jalr => special(9),
+        R::Syscall => (sa & 0xFFFFF) << 6 | 12,
+        R::Break => (sa & 0xFFFFF) << 6 | 13,
+        R::Sync => special(15),
+        R::Mfhi => special(16),
+        R::Mthi => special(17),
+        R::Mflo => special(18),
+        R::Mtlo => special(19),
+        R::Dsllv => special(20),
+        R::Dsrlv => special(22),
+        R::Dsrav => special(23),
+        R::Mult => special(24),
+        R::Multu => special(25),
+        R::Div => special(26),
+        R::Divu => special(27),
+        R::Dmult => special(28),
+        R::Dmultu => special(29),
+        R::Ddiv => special(30),
+        R::Ddivu => special(31),
+        R::Add => special(32),
+        R::Addu => special(33),
+        R::Sub => special(34),
+        R::Subu => special(35),
+        R::And => special(36),
+        R::Or => special(37),
+        R::Xor => special(38),
+        R::Nor => special(39),
+        R::Slt => special(42),
+        R::Sltu => special(43),
+        R::Dadd => special(44),
+        R::Daddu => special(45),
+        R::Dsub => special(46),
+        R::Dsubu => special(47),
+        R::Tge => special(48),
+        R::Tgeu => special(49),
+        R::Tlt => special(50),
+        R::Tltu => special(51),
+        R::Teq => special(52),
+        R::Tne => special(54),
+        R::Dsll => special(56),
+        R::Dsrl => special(58),
+        R::Dsra => special(59),
+        R::Dsll32 => special(60),
+        R::Dsrl32 => special(62),
+        R::Dsra32 => special(63),
+        R::Mfc0 => cop(16, 0),
+        R::Dmfc0 => cop(16, 1),
+        R::Cfc0 => cop(16, 2),
+        R::Mtc0 => cop(16, 4),
+        R::Dmtc0 => cop(16, 5),
+        R::Ctc0 => cop(16, 6),
+        R::Tlbr => 16 << 26 | 1 << 25 | 1,
+        R::Tlbwi => 16 << 26 | 1 << 25 | 2,
+        R::Tlbwr => 16 << 26 | 1 << 25 | 6,
+        R::Tlbp => 16 << 26 | 1 << 25 | 8,
+        R::Eret => 16 << 26 | 1 << 25 | 24,
+        R::Mfc1 => cop(17, 0),
+        R::Dmfc1 => cop(17, 1),
+        R::Cfc1 => cop(17, 2),
+        R::Mtc1 => cop(17, 4),
+        R::Dmtc1 => cop(17, 5),
+        R::Ctc1 => cop(17, 6),
+        R::Mfc2 => cop(18, 0) | (sa & 0xF) << 7,
+        R::Cfc2 => cop(18, 2),
+        R::Mtc2 => cop(18, 4) | (sa & 0xF) << 7,
+        R::Ctc2 => cop(18, 6),
+        R::AddS => float(16, 0),
+        R::SubS => float(16, 1),
+        R::MulS => float(16, 2),
+        R::DivS => float(16, 3),
+        R::SqrtS => float(16, 4),
+        R::AbsS => float(16, 5),
+        R::MovS => float(16, 6),
+        R::NegS => float(16, 7),
+        R::RoundLS => float(16, 8),
+        R::TruncLS => float(16, 9),
+        R::CeilLS => float(16, 10),
+        R::FloorLS => float(16, 11),
+        R::RoundWS => float(16, 12),
+        R::TruncWS => float(16, 13),
+        R::CeilWS => float(16, 14),
+        R::FloorWS => float(16, 15),
+        R::CvtDS => float(16, 33),
+        R::CvtWS => float(16, 36),
+        R::CvtLS => float(16, 37),
+        R::Cs => float(16, 48 | (sa & 0xF)),
+        R::AddD => float(17, 0),
+        R::SubD => float(17, 1),
+        R::MulD => float(17, 2),
+        R::DivD => float(17, 3),
+        R::SqrtD => float(17, 4),
+        R::AbsD => float(17, 5),
+        R::MovD => float(17, 6),
+        R::NegD => float(17, 7),
+        R::RoundLD => float(17, 8),
+        R::TruncLD => float(17, 9),
+        R::CeilLD => float(17, 10),
+        R::FloorLD => float(17, 11),
+        R::RoundWD => float(17, 12),
+        R::TruncWD => float(17, 13),
+        R::CeilWD => float(17, 14),
+        R::FloorWD => float(17, 15),
+        R::CvtSD => float(17, 32),
+        R::CvtWD => float(17, 36),
+        R::CvtLD => float(17, 37),
+        R::Cd => float(17, 48 | (sa & 0xF)),
+        R::CvtSW => float(20, 32),
+        R::CvtDW => float(20, 33),
+        R::CvtSL => float(21, 32),
+        R::CvtDL => float(21, 33),
+        e => panic!("Pseudoinstruction {:?} can not be encoded", e),
+    }
+}
+
+#[rustfmt::skip]
+fn encode_vector(op: ast::VTypeOp, vd: u32, vs: u32, vt: u32, de: u32, e: u32) -> u32 {
+    let funct = match op {
+        V::Vmulf => 0b000000,
+        V::Vmulu => 0b000001,
+        V::Vrndp => 0b000010,
+        V::Vmulq => 0b000011,
+        V::Vmudl => 0b000100,
+        V::Vmudm => 0b000101,
+        V::Vmudn => 0b000110,
+        V::Vmudh => 0b000111,
+        V::Vmacf => 0b001000,
+        V::Vmacu => 0b001001,
+        V::Vrndn => 0b001010,
+        V::Vmacq => 0b001011,
+        V::Vmadl => 0b001100,
+        V::Vmadm => 0b001101,
+        V::Vmadn => 0b001110,
+        V::Vmadh => 0b001111,
+        V::Vadd => 0b010000,
+        V::Vsub => 0b010001,
+        V::Vabs => 0b010011,
+        V::Vaddc => 0b010100,
+        V::Vsubc => 0b010101,
+        V::Vsar => 0b011101,
+        V::Vlt => 0b100000,
+        V::Veq => 0b100001,
+        V::Vne => 0b100010,
+        V::Vge => 0b100011,
+        V::Vcl => 0b100100,
+        V::Vch => 0b100101,
+        V::Vcr => 0b100110,
+        V::Vmrg => 0b100111,
+        V::Vand => 0b101000,
+        V::Vnand => 0b101001,
+        V::Vor => 0b101010,
+        V::Vnor => 0b101011,
+        V::Vxor => 0b101100,
+        V::Vnxor => 0b101101,
+        V::Vrcp => 0b110000,
+        V::Vrcpl => 0b110001,
+        V::Vrcph => 0b110010,
+        V::Vmov => 0b110011,
+        V::Vrsq => 0b110100,
+        V::Vrsql => 0b110101,
+        V::Vrsqh => 0b110110,
+        V::Vnop => 0b110111,
+    };
+    // The single lane instructions keep the destination element in the vs field
+    let vs = match op {
+        V::Vrcp | V::Vrcpl | V::Vrcph | V::Vrsq | V::Vrsql | V::Vrsqh => de,
+        _ => vs,
+    };
+    18 << 26 | 1 << 25 | (e & 0xF) << 21 | vt << 16 | (vs & 0x1F) << 11 | vd << 6 | funct
+}
+
+#[rustfmt::skip]
+fn encode_vector_memory(op: ast::VMemTypeOp, vt: u32, e: u32, offset: u32, base: u32) -> u32 {
+    let (opcode, funct) = match op {
+        VM::Lbv => (50, 0),
+        VM::Lsv => (50, 1),
+        VM::Llv => (50, 2),
+        VM::Ldv => (50, 3),
+        VM::Lqv => (50, 4),
+        VM::Lrv => (50, 5),
+        VM::Lpv => (50, 6),
+        VM::Luv => (50, 7),
+        VM::Lhv => (50, 8),
+        VM::Lfv => (50, 9),
+        VM::Ltv => (50, 11),
+        VM::Sbv => (58, 0),
+        VM::Ssv => (58, 1),
+        VM::Slv => (58, 2),
+        VM::Sdv => (58, 3),
+        VM::Sqv => (58, 4),
+        VM::Srv => (58, 5),
+        VM::Spv => (58, 6),
+        VM::Suv => (58, 7),
+        VM::Shv => (58, 8),
+        VM::Sfv => (58, 9),
+        VM::Swv => (58, 10),
+        VM::Stv => (58, 11),
+    };
+    opcode << 26 | base << 21 | vt << 16 | funct << 11 | (e & 0xF) << 7 | (offset & 0x7F)
+}
@@ -1,21 +1,42 @@
+//! The instructions produced by [`Mipsasm::decode`](crate::Mipsasm::decode).
+
+use crate::target::Target as Cpu;
+
 use alloc::{
+    collections::BTreeMap,
     format,
     string::{String, ToString},
 };
 use core::convert::{From, TryFrom};
 use core::fmt;
+use core::ops::Deref;
 use core::str::FromStr;
 use strum_macros::{Display, EnumString};
 
-#[derive(Debug)]
+/// Error returned when a register, or another operand encoded in a register
+/// field, can not be parsed or converted
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum RegParseError {
     RegParseError(String),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+impl fmt::Display for RegParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegParseError::RegParseError(reg) => write!(f, "invalid register `{}`", reg),
+        }
+    }
+}
+
+/// The 28 bit jump target, as encoded in the instruction shifted left by 2
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Target(pub u32);
 
-#[derive(Debug, PartialEq, Eq)]
+// The RSP program counter is 12 bits, so control transfers wrap around inside IMEM
+const IMEM_MASK: u32 = 0xFFF;
+
+/// The raw 16 bit immediate field, branch offsets are counted in words
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct Immediate(pub u16);
 
 struct Signed(u16);
@@ -25,23 +46,32 @@ impl fmt::LowerHex for Signed {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let val = self.0 as i16;
         let p = if f.alternate() { "0x" } else { "" };
-        let x = format!("{:x}", val.abs());
+        let x = format!("{:x}", val.unsigned_abs());
         f.pad_integral(val >= 0, p, &x)
     }
 }
 
-#[derive(PartialEq, Eq)]
+/// A decoded instruction.
+///
+/// Operands are stored as the fields they are encoded in, operands that are
+/// not general purpose registers are converted from the register they share
+/// a field with.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum Instruction {
+    /// Instructions with a 16 bit immediate, loads, stores and branches
     Immediate {
         op: ITypeOp,
         rs: Register,
         rt: Register,
         imm: Immediate,
     },
-    Jump {
-        op: JTypeOp,
-        target: Target,
-    },
+    /// `j` and `jal`
+    Jump { op: JTypeOp, target: Target },
+    /// Register to register instructions.
+    ///
+    /// FPU instructions keep `fs` in `rs` and `fd` in `rd`, float compares keep
+    /// the [`FloatCond`] in `sa`. `mfc2`/`mtc2` keep the vector register in
+    /// `rd` and its element in `sa`.
     Register {
         op: RTypeOp,
         rs: Register,
@@ -49,6 +79,10 @@ pub enum Instruction {
         rd: Register,
         sa: u32,
     },
+    /// Vector unit computational instructions.
+    ///
+    /// `e` is the element selector of `vt`, the reciprocal instructions keep
+    /// the element of `vd` they write in `de` and leave `vs` unused.
     Vector {
         op: VTypeOp,
         vd: VuRegister,
@@ -57,288 +91,625 @@ pub enum Instruction {
         de: u32,
         e: u32,
     },
+    /// Vector unit loads and stores.
+    ///
+    /// `offset` is the raw 7 bit field, scaled by [`VMemTypeOp::scale`].
+    VectorMemory {
+        op: VMemTypeOp,
+        vt: VuRegister,
+        e: u32,
+        offset: u32,
+        base: Register,
+    },
 }
 
 type I = ITypeOp;
 type R = RTypeOp;
 type V = VTypeOp;
 
+impl Instruction {
+    /// Returns the address a jump or branch at `pc` transfers control to.
+    ///
+    /// On the RSP `base` is the address IMEM is mapped at and the target wraps
+    /// around inside it, on the VR4300 jumps stay in the 256 MiB region of `pc`.
+    pub fn target(&self, pc: u32, base: u32, cpu: Cpu) -> Option<u32> {
+        let next = pc.wrapping_add(4);
+        let target = match self {
+            Instruction::Jump {
+                target: Target(target),
+                ..
+            } => match cpu {
+                Cpu::Rsp => *target,
+                Cpu::Vr4300 => return Some((next & 0xF000_0000) | target),
+            },
+            Instruction::Immediate {
+                op,
+                imm: Immediate(imm),
+                ..
+            } if op.is_branch() => next.wrapping_add((*imm as i16 as u32) << 2),
+            _ => return None,
+        };
+        match cpu {
+            Cpu::Rsp => Some((base & !IMEM_MASK) | (target & IMEM_MASK)),
+            Cpu::Vr4300 => Some(target),
+        }
+    }
+
+    /// Returns whether the instruction is a jump or branch, which executes the
+    /// instruction after it before transferring control
+    pub fn has_delay_slot(&self) -> bool {
+        match self {
+            Instruction::Jump { .. } => true,
+            Instruction::Immediate { op, .. } => op.is_branch(),
+            Instruction::Register { op, .. } => matches!(op, R::Jr | R::Jalr),
+            _ => false,
+        }
+    }
+
+    /// Encodes the instruction to its machine word, `None` for a pseudoinstruction.
+    ///
+    /// Every instruction returned by [`Mipsasm::decode`](crate::Mipsasm::decode)
+    /// encodes back to the word it was decoded from.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::ast::{Instruction, RTypeOp, Register};
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let words = [0x00850018, 0xc8812001, 0x4aa31054];
+    /// for (inst, word) in mipsasm.decode(&words).iter().zip(words) {
+    ///     assert_eq!(inst.as_ref().unwrap().encode(), Some(word));
+    /// }
+    ///
+    /// let (rs, rt, rd) = (Register::A1, Register::Zero, Register::A0);
+    /// let inst = Instruction::Register { op: RTypeOp::Move, rs, rt, rd, sa: 0 };
+    /// assert_eq!(inst.encode(), None);
+    /// ```
+    pub fn encode(&self) -> Option<u32> {
+        match self {
+            Instruction::Immediate { op, .. } if op.is_pseudo() => None,
+            Instruction::Register { op, .. } if op.is_pseudo() => None,
+            inst => Some(crate::assembler::encode(inst)),
+        }
+    }
+
+    /// Displays the instruction as located at `pc`, resolving jump and branch
+    /// targets to an address or to one of `labels`
+    pub fn at<'a>(
+        &'a self,
+        pc: u32,
+        base: u32,
+        cpu: Cpu,
+        labels: &'a BTreeMap<u32, String>,
+    ) -> Located<'a> {
+        Located {
+            inst: self,
+            pc,
+            base,
+            cpu,
+            labels,
+            half: None,
+            aliases: &NO_ALIASES,
+        }
+    }
+}
+
+/// Names shown in place of registers when formatting instructions, the
+/// `.alias` directives of the source a listing was assembled from
+///
+/// # Examples
+///
+/// ```
+/// use mipsasm_rsp::ast::{Aliases, Register, VuRegister};
+///
+/// let mut aliases = Aliases::new();
+/// aliases.register("ptr", Register::A0).vu_register("vzero", VuRegister::V0);
+/// assert_eq!(aliases.find_register("ptr"), Some(Register::A0));
+/// assert_eq!(aliases.find_vu_register("ptr"), None);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Aliases {
+    registers: BTreeMap<Register, String>,
+    vu_registers: BTreeMap<VuRegister, String>,
+}
+
+static NO_ALIASES: Aliases = Aliases::new();
+
+impl Aliases {
+    /// Creates an empty set of aliases
+    pub const fn new() -> Aliases {
+        Aliases {
+            registers: BTreeMap::new(),
+            vu_registers: BTreeMap::new(),
+        }
+    }
+
+    /// Names the general purpose register `reg`, replacing its previous name
+    pub fn register(&mut self, name: &str, reg: Register) -> &mut Aliases {
+        self.registers.insert(reg, name.to_string());
+        self
+    }
+
+    /// Names the vector unit register `reg`, replacing its previous name
+    pub fn vu_register(&mut self, name: &str, reg: VuRegister) -> &mut Aliases {
+        self.vu_registers.insert(reg, name.to_string());
+        self
+    }
+
+    /// Returns the general purpose register named `name`
+    pub fn find_register(&self, name: &str) -> Option<Register> {
+        find(&self.registers, name)
+    }
+
+    /// Returns the vector unit register named `name`
+    pub fn find_vu_register(&self, name: &str) -> Option<VuRegister> {
+        find(&self.vu_registers, name)
+    }
+
+    fn named_gpr(&self, reg: Register) -> Named<'_, Register> {
+        Named {
+            reg,
+            alias: self.registers.get(&reg).map(String::as_str),
+        }
+    }
+
+    fn named_vreg(&self, reg: VuRegister) -> Named<'_, VuRegister> {
+        Named {
+            reg,
+            alias: self.vu_registers.get(&reg).map(String::as_str),
+        }
+    }
+}
+
+fn find<T: Copy>(names: &BTreeMap<T, String>, name: &str) -> Option<T> {
+    names.iter().find(|(_, x)| *x == name).map(|(reg, _)| *reg)
+}
+
+// A register formatted as its alias, if it has one
+#[derive(Clone, Copy)]
+struct Named<'a, T> {
+    reg: T,
+    alias: Option<&'a str>,
+}
+
+impl<T: fmt::Display> fmt::Display for Named<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.alias {
+            Some(alias) => write!(f, "{}", alias),
+            None => write!(f, "{}", self.reg),
+        }
+    }
+}
+
+impl<T> Deref for Named<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.reg
+    }
+}
+
+/// The half of an address built by a `lui` and an instruction adding the
+/// lower 16 bits to its register
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Half {
+    /// The `lui`, shown as `%hi(address)`
+    Hi,
+    /// The instruction adding the lower half, shown as `%lo(address)`
+    Lo,
+}
+
+/// An instruction formatted at its address, see [`Instruction::at`]
+pub struct Located<'a> {
+    inst: &'a Instruction,
+    pc: u32,
+    base: u32,
+    cpu: Cpu,
+    labels: &'a BTreeMap<u32, String>,
+    half: Option<(Half, u32)>,
+    aliases: &'a Aliases,
+}
+
+impl<'a> Located<'a> {
+    /// Displays the immediate as `half` of `addr`, or of one of the labels
+    pub fn half(mut self, half: Half, addr: u32) -> Self {
+        self.half = Some((half, addr));
+        self
+    }
+
+    /// Displays the registers named in `aliases` by their alias
+    pub fn aliases(mut self, aliases: &'a Aliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+}
+
+impl fmt::Display for Located<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let (Some((half, addr)), Instruction::Immediate { op, rs, rt, .. }) =
+            (self.half, self.inst)
+        {
+            let (rs, rt) = (self.aliases.named_gpr(*rs), self.aliases.named_gpr(*rt));
+            let symbol = match self.labels.get(&addr) {
+                Some(label) => label.clone(),
+                None => format!("{:#x}", addr),
+            };
+            let imm = match half {
+                Half::Hi => format!("%hi({})", symbol),
+                Half::Lo => format!("%lo({})", symbol),
+            };
+            return match op {
+                I::Lui => write!(f, "{:7} {}, {}", op, rt, imm),
+                I::Addi | I::Addiu | I::Daddi | I::Daddiu | I::Ori => {
+                    write!(f, "{:7} {}, {}, {}", op, rt, rs, imm)
+                }
+                I::Ldc1 | I::Lwc1 | I::Sdc1 | I::Swc1 => {
+                    let ft = FloatRegister::from(*rt);
+                    write!(f, "{:7} {}, {}({})", op, ft, imm, rs)
+                }
+                _ => write!(f, "{:7} {}, {}({})", op, rt, imm, rs),
+            };
+        }
+
+        let target = match self.inst.target(self.pc, self.base, self.cpu) {
+            Some(target) => target,
+            None => return self.inst.write(f, self.aliases),
+        };
+        let target = match self.labels.get(&target) {
+            Some(label) => label.clone(),
+            None => format!("{:#x}", target),
+        };
+
+        match self.inst {
+            Instruction::Jump { op, .. } => write!(f, "{:7}{}", op, target),
+            Instruction::Immediate { op, rs, rt, .. } => {
+                let (rs, rt) = (self.aliases.named_gpr(*rs), self.aliases.named_gpr(*rt));
+                fmt_branch(f, op, rs, rt, format_args!("{}", target))
+            }
+            inst => inst.write(f, self.aliases),
+        }
+    }
+}
+
+fn fmt_branch(
+    f: &mut fmt::Formatter,
+    op: &ITypeOp,
+    rs: Named<'_, Register>,
+    rt: Named<'_, Register>,
+    target: fmt::Arguments,
+) -> fmt::Result {
+    match op {
+        I::B | I::Bal => write!(f, "{:7} {}", op, target),
+        I::Beq
+        | I::Beql
+        | I::Bne
+        | I::Bnel
+        | I::Bge
+        | I::Bgt
+        | I::Ble
+        | I::Blt
+        | I::Bgeu
+        | I::Bgtu
+        | I::Bleu
+        | I::Bltu
+        | I::Bgel
+        | I::Bgtl
+        | I::Blel
+        | I::Bltl
+        | I::Bgeul
+        | I::Bgtul
+        | I::Bleul
+        | I::Bltul => write!(f, "{:7} {}, {}, {}", op, rs, rt, target),
+        I::Bc0f | I::Bc1f | I::Bc0fl | I::Bc1fl | I::Bc0t | I::Bc1t | I::Bc0tl | I::Bc1tl => {
+            write!(f, "{:7}{}", op, target)
+        }
+        _ => write!(f, "{:7} {}, {}", op, rs, target),
+    }
+}
+
 impl fmt::Display for Instruction {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, &Aliases::new())
+    }
+}
+
+impl Instruction {
+    fn write(&self, f: &mut fmt::Formatter, aliases: &Aliases) -> fmt::Result {
         match &self {
             Instruction::Immediate {
                 op,
                 rs,
                 rt,
                 imm: Immediate(imm),
-            } => match op {
-                I::Lb
-                | I::Lbu
-                | I::Ld
-                | I::Ldl
-                | I::Ldr
-                | I::Lh
-                | I::Lhu
-                | I::Ll
-                | I::Lld
-                | I::Lw
-                | I::Lwl
-                | I::Lwr
-                | I::Lwu
-                | I::Sb
-                | I::Sc
-                | I::Scd
-                | I::Sd
-                | I::Sdl
-                | I::Sdr
-                | I::Sh
-                | I::Sw
-                | I::Swl
-                | I::Swr => {
-                    write!(f, "{:7} {}, {:#x}({})", op, rt, Signed(*imm), rs)
-                }
-                I::Lbv => {
-                    write!(
-                        f,
-                        "{:7} {}[{}], {:#x}({})",
-                        op,
-                        rt,
-                        *imm >> 7,
-                        *imm & 0b1111111,
-                        rs
-                    )
-                }
-                I::Cache => {
-                    write!(
-                        f,
-                        "{:7} {:#x}, {:#x}({})",
-                        op,
-                        rt.as_num(),
-                        Signed(*imm),
-                        rs
-                    )
-                }
-                I::Addi | I::Addiu | I::Daddi | I::Daddiu | I::Slti | I::Sltiu => {
-                    write!(f, "{:7} {}, {}, {:#x}", op, rt, rs, Signed(*imm))
-                }
-                I::Andi | I::Ori | I::Xori => write!(f, "{:7} {}, {}, {:#x}", op, rt, rs, imm),
-                I::Lui => write!(f, "{:7} {}, {:#x}", op, rt, imm),
-                I::Beqz | I::Bgtz | I::Bgtzl | I::Blez | I::Blezl | I::Bnez => {
-                    write!(f, "{:7} {}, {:#x}", op, rs, Signed(*imm))
-                }
-                I::Beq | I::Beql | I::Bne | I::Bnel => {
-                    write!(f, "{:7} {}, {}, {:#x}", op, rs, rt, Signed(*imm))
-                }
-                I::Bgez
-                | I::Bgezal
-                | I::Bgezall
-                | I::Bgezl
-                | I::Bltz
-                | I::Bltzal
-                | I::Bltzall
-                | I::Bltzl
-                | I::Teqi
-                | I::Tgei
-                | I::Tgeiu
-                | I::Tlti
-                | I::Tltiu
-                | I::Tnei => {
-                    write!(f, "{:7} {}, {:#x}", op, rs, Signed(*imm))
-                }
-                I::Bc0f
-                | I::Bc1f
-                | I::Bc0fl
-                | I::Bc1fl
-                | I::Bc0t
-                | I::Bc1t
-                | I::Bc0tl
-                | I::Bc1tl => {
-                    write!(f, "{:7}{:#x}", op, Signed(*imm))
-                }
-                I::Ldc1 | I::Lwc1 | I::Sdc1 | I::Swc1 => {
-                    write!(
-                        f,
-                        "{:7} {}, {:#x}({})",
-                        op,
-                        FloatRegister::from(*rt),
-                        Signed(*imm),
-                        rs
-                    )
+            } => {
+                let (rs, rt) = (aliases.named_gpr(*rs), aliases.named_gpr(*rt));
+                match op {
+                    I::Lb
+                    | I::Lbu
+                    | I::Ld
+                    | I::Ldl
+                    | I::Ldr
+                    | I::Lh
+                    | I::Lhu
+                    | I::Ll
+                    | I::Lld
+                    | I::Lw
+                    | I::Lwl
+                    | I::Lwr
+                    | I::Lwu
+                    | I::Sb
+                    | I::Sc
+                    | I::Scd
+                    | I::Sd
+                    | I::Sdl
+                    | I::Sdr
+                    | I::Sh
+                    | I::Sw
+                    | I::Swl
+                    | I::Swr => {
+                        write!(f, "{:7} {}, {:#x}({})", op, rt, Signed(*imm), rs)
+                    }
+                    I::Cache => {
+                        write!(
+                            f,
+                            "{:7} {:#x}, {:#x}({})",
+                            op,
+                            rt.as_num(),
+                            Signed(*imm),
+                            rs
+                        )
+                    }
+                    I::Addi | I::Addiu | I::Daddi | I::Daddiu | I::Slti | I::Sltiu => {
+                        write!(f, "{:7} {}, {}, {:#x}", op, rt, rs, Signed(*imm))
+                    }
+                    I::Andi | I::Ori | I::Xori => write!(f, "{:7} {}, {}, {:#x}", op, rt, rs, imm),
+                    I::Lui => write!(f, "{:7} {}, {:#x}", op, rt, imm),
+                    I::Li | I::Dli => write!(f, "{:7} {}, {:#x}", op, rt, Signed(*imm)),
+                    I::Lli => write!(f, "{:7} {}, {:#x}", op, rt, imm),
+                    I::Subi | I::Subiu | I::Dsubi | I::Dsubiu => {
+                        write!(f, "{:7} {}, {}, {:#x}", op, rt, rs, Signed(*imm))
+                    }
+                    I::Teqi | I::Tgei | I::Tgeiu | I::Tlti | I::Tltiu | I::Tnei => {
+                        write!(f, "{:7} {}, {:#x}", op, rs, Signed(*imm))
+                    }
+                    I::Ldc1 | I::Lwc1 | I::Sdc1 | I::Swc1 => {
+                        write!(
+                            f,
+                            "{:7} {}, {:#x}({})",
+                            op,
+                            FloatRegister::from(*rt),
+                            Signed(*imm),
+                            rs
+                        )
+                    }
+                    _ => fmt_branch(f, op, rs, rt, format_args!("{:#x}", Signed(*imm))),
                 }
-                e => panic!("Unhandled immediate instruction: {:?}", e),
-            },
+            }
             Instruction::Jump {
                 op,
                 target: Target(target),
             } => {
                 write!(f, "{:7}{:#X?}", op, target)
             }
-            Instruction::Register { op, rs, rt, rd, sa } => match op {
-                R::Sync => write!(f, "{}", op),
-                R::Add
-                | R::Addu
-                | R::And
-                | R::Dadd
-                | R::Daddu
-                | R::Dsub
-                | R::Dsubu
-                | R::Nor
-                | R::Or
-                | R::Slt
-                | R::Sltu
-                | R::Sub
-                | R::Subu
-                | R::Xor => {
-                    write!(f, "{:7} {}, {}, {}", op, rd, rs, rt)
-                }
-                R::Dsll
-                | R::Dsll32
-                | R::Dsra
-                | R::Dsra32
-                | R::Dsrl
-                | R::Dsrl32
-                | R::Sll
-                | R::Sra
-                | R::Srl => {
-                    write!(f, "{:7} {}, {}, {:#x?}", op, rd, rt, sa)
-                }
-                R::Dsllv | R::Dsrav | R::Dsrlv | R::Sllv | R::Srav | R::Srlv => {
-                    write!(f, "{:7} {}, {}, {}", op, rd, rt, rs)
-                }
-                R::Break | R::Syscall => {
-                    if *sa == 0 {
-                        write!(f, "{}", op)
-                    } else {
-                        write!(f, "{:7} {:#x?}", op, sa)
+            Instruction::Register { op, rs, rt, rd, sa } => {
+                let (rs, rt, rd) = (
+                    aliases.named_gpr(*rs),
+                    aliases.named_gpr(*rt),
+                    aliases.named_gpr(*rd),
+                );
+                match op {
+                    R::Sync | R::Nop => write!(f, "{}", op),
+                    R::Clear => write!(f, "{:7} {}", op, rd),
+                    R::Move | R::Dmove | R::Not | R::Abs | R::Dabs => {
+                        write!(f, "{:7} {}, {}", op, rd, rs)
                     }
-                }
-                R::Ddiv
-                | R::Ddivu
-                | R::Div
-                | R::Divu
-                | R::Dmult
-                | R::Dmultu
-                | R::Mult
-                | R::Multu
-                | R::Teq
-                | R::Tge
-                | R::Tgeu
-                | R::Tlt
-                | R::Tltu
-                | R::Tne => {
-                    write!(f, "{:7} {}, {}", op, rs, rt)
-                }
-                R::Jalr => {
-                    if let &Register::Ra = rd {
+                    R::Neg | R::Negu | R::Dneg | R::Dnegu => write!(f, "{:7} {}, {}", op, rd, rt),
+                    R::Mul
+                    | R::Mulu
+                    | R::Mulo
+                    | R::Mulou
+                    | R::Dmul
+                    | R::Dmulu
+                    | R::Dmulo
+                    | R::Dmulou
+                    | R::Rem
+                    | R::Remu
+                    | R::Drem
+                    | R::Dremu
+                    | R::Drol
+                    | R::Dror
+                    | R::Seq
+                    | R::Sge
+                    | R::Sgeu
+                    | R::Sgt
+                    | R::Sgtu
+                    | R::Sle
+                    | R::Sleu
+                    | R::Sne
+                    | R::Add
+                    | R::Addu
+                    | R::And
+                    | R::Dadd
+                    | R::Daddu
+                    | R::Dsub
+                    | R::Dsubu
+                    | R::Nor
+                    | R::Or
+                    | R::Slt
+                    | R::Sltu
+                    | R::Sub
+                    | R::Subu
+                    | R::Xor => {
+                        write!(f, "{:7} {}, {}, {}", op, rd, rs, rt)
+                    }
+                    R::Dsll
+                    | R::Dsll32
+                    | R::Dsra
+                    | R::Dsra32
+                    | R::Dsrl
+                    | R::Dsrl32
+                    | R::Sll
+                    | R::Sra
+                    | R::Srl => {
+                        write!(f, "{:7} {}, {}, {:#x?}", op, rd, rt, sa)
+                    }
+                    R::Dsllv | R::Dsrav | R::Dsrlv | R::Sllv | R::Srav | R::Srlv => {
+                        write!(f, "{:7} {}, {}, {}", op, rd, rt, rs)
+                    }
+                    R::Break | R::Syscall => {
+                        if *sa == 0 {
+                            write!(f, "{}", op)
+                        } else {
+                            write!(f, "{:7} {:#x?}", op, sa)
+                        }
+                    }
+                    R::Ddiv
+                    | R::Ddivu
+                    | R::Div
+                    | R::Divu
+                    | R::Dmult
+                    | R::Dmultu
+                    | R::Mult
+                    | R::Multu
+                    | R::Teq
+                    | R::Tge
+                    | R::Tgeu
+                    | R::Tlt
+                    | R::Tltu
+                    | R::Tne => {
+                        write!(f, "{:7} {}, {}", op, rs, rt)
+                    }
+                    R::Jalr => {
+                        if *rd == Register::Ra {
+                            write!(f, "{:7} {}", op, rs)
+                        } else {
+                            write!(f, "{:7} {},  {}", op, rd, rs)
+                        }
+                    }
+                    R::Jr | R::Mthi | R::Mtlo => {
                         write!(f, "{:7} {}", op, rs)
-                    } else {
-                        write!(f, "{:7} {},  {}", op, rd, rs)
+                    }
+                    R::Mfhi | R::Mflo => {
+                        write!(f, "{:7} {}", op, rd)
+                    }
+                    R::Cfc0 | R::Ctc0 | R::Dmfc0 | R::Dmtc0 | R::Mfc0 | R::Mtc0 => {
+                        match Cop0Register::try_from(rd.as_num()) {
+                            Ok(reg) => write!(f, "{:7} {}, {}", op, rt, reg),
+                            Err(_) => write!(f, "{:7} {}, ${}", op, rt, rd.as_num()),
+                        }
+                    }
+                    R::Cfc1 | R::Ctc1 | R::Dmfc1 | R::Dmtc1 | R::Mfc1 | R::Mtc1 => {
+                        write!(f, "{:7} {}, {}", op, rt, FloatRegister::from(*rd))
+                    }
+                    R::Mfc2 | R::Mtc2 => {
+                        let vd = aliases.named_vreg(VuRegister::from(*rd));
+                        write!(f, "{:7} {}, {}[{}]", op, rt, vd, sa)
+                    }
+                    R::Cfc2 | R::Ctc2 => match VuControlRegister::try_from(rd.as_num()) {
+                        Ok(reg) => write!(f, "{:7} {}, {}", op, rt, reg),
+                        Err(_) => write!(f, "{:7} {}, ${}", op, rt, rd.as_num()),
+                    },
+                    R::Eret | R::Tlbp | R::Tlbr | R::Tlbwi | R::Tlbwr => {
+                        write!(f, "{}", op)
+                    }
+                    R::AddS
+                    | R::AddD
+                    | R::SubS
+                    | R::SubD
+                    | R::MulS
+                    | R::MulD
+                    | R::DivS
+                    | R::DivD => {
+                        let x = op.to_string().replace('_', ".");
+                        write!(
+                            f,
+                            "{:7} {},  {},  {}",
+                            x,
+                            FloatRegister::from(*rd),
+                            FloatRegister::from(*rs),
+                            FloatRegister::from(*rt)
+                        )
+                    }
+                    R::AbsS
+                    | R::AbsD
+                    | R::CvtDS
+                    | R::CvtDW
+                    | R::CvtDL
+                    | R::CvtLS
+                    | R::CvtLD
+                    | R::CvtSD
+                    | R::CvtSW
+                    | R::CvtSL
+                    | R::CvtWD
+                    | R::CvtWS
+                    | R::MovS
+                    | R::MovD
+                    | R::NegS
+                    | R::NegD
+                    | R::SqrtS
+                    | R::SqrtD => {
+                        let x = op.to_string().replace('_', ".");
+                        write!(
+                            f,
+                            "{:7} {},  {}",
+                            x,
+                            FloatRegister::from(*rd),
+                            FloatRegister::from(*rs)
+                        )
+                    }
+                    R::CeilLS | R::CeilLD | R::CeilWS | R::CeilWD => {
+                        let x = op.to_string().replace('_', ".");
+                        write!(
+                            f,
+                            "{} {}, {}",
+                            x,
+                            FloatRegister::from(*rd),
+                            FloatRegister::from(*rs)
+                        )
+                    }
+                    R::FloorLS
+                    | R::FloorLD
+                    | R::FloorWS
+                    | R::FloorWD
+                    | R::RoundLS
+                    | R::RoundLD
+                    | R::RoundWS
+                    | R::RoundWD
+                    | R::TruncLS
+                    | R::TruncLD
+                    | R::TruncWS
+                    | R::TruncWD => {
+                        let x = op.to_string().replace('_', ".");
+                        write!(
+                            f,
+                            "{} {}, {}",
+                            x,
+                            FloatRegister::from(*rd),
+                            FloatRegister::from(*rs)
+                        )
+                    }
+                    R::Cs => {
+                        write!(
+                            f,
+                            "c.{}.s {}, {}",
+                            FloatCond::try_from(*sa).map_err(|_| fmt::Error)?,
+                            FloatRegister::from(*rs),
+                            FloatRegister::from(*rt)
+                        )
+                    }
+                    R::Cd => {
+                        write!(
+                            f,
+                            "c.{}.d {}, {}",
+                            FloatCond::try_from(*sa).map_err(|_| fmt::Error)?,
+                            FloatRegister::from(*rs),
+                            FloatRegister::from(*rt)
+                        )
                     }
                 }
-                R::Jr | R::Mthi | R::Mtlo => {
-                    write!(f, "{:7} {}", op, rs)
-                }
-                R::Mfhi | R::Mflo => {
-                    write!(f, "{:7} {}", op, rd)
-                }
-                R::Cfc0 | R::Ctc0 | R::Dmfc0 | R::Dmtc0 | R::Mfc0 | R::Mtc0 => {
-                    write!(f, "{:7} {}, {}", op, rt, Cop0Register::from(*rd))
-                }
-                R::Cfc1 | R::Ctc1 | R::Dmfc1 | R::Dmtc1 | R::Mfc1 | R::Mtc1 => {
-                    write!(f, "{:7} {}, {}", op, rt, FloatRegister::from(*rd))
-                }
-                R::Eret | R::Tlbp | R::Tlbr | R::Tlbwi | R::Tlbwr => {
-                    write!(f, "{}", op)
-                }
-                R::AddS | R::AddD | R::SubS | R::SubD | R::MulS | R::MulD | R::DivS | R::DivD => {
-                    let x = op.to_string().replace('_', ".");
-                    write!(
-                        f,
-                        "{:7} {},  {},  {}",
-                        x,
-                        FloatRegister::from(*rd),
-                        FloatRegister::from(*rs),
-                        FloatRegister::from(*rt)
-                    )
-                }
-                R::AbsS
-                | R::AbsD
-                | R::CvtDS
-                | R::CvtDW
-                | R::CvtDL
-                | R::CvtLS
-                | R::CvtLD
-                | R::CvtSD
-                | R::CvtSW
-                | R::CvtSL
-                | R::CvtWD
-                | R::CvtWS
-                | R::MovS
-                | R::MovD
-                | R::NegS
-                | R::NegD
-                | R::SqrtS
-                | R::SqrtD => {
-                    let x = op.to_string().replace('_', ".");
-                    write!(
-                        f,
-                        "{:7} {},  {}",
-                        x,
-                        FloatRegister::from(*rd),
-                        FloatRegister::from(*rs)
-                    )
-                }
-                R::CeilLS | R::CeilLD | R::CeilWS | R::CeilWD => {
-                    let x = op.to_string().replace('_', ".");
-                    write!(
-                        f,
-                        "{} {}, {}",
-                        x,
-                        FloatRegister::from(*rd),
-                        FloatRegister::from(*rs)
-                    )
-                }
-                R::FloorLS
-                | R::FloorLD
-                | R::FloorWS
-                | R::FloorWD
-                | R::RoundLS
-                | R::RoundLD
-                | R::RoundWS
-                | R::RoundWD
-                | R::TruncLS
-                | R::TruncLD
-                | R::TruncWS
-                | R::TruncWD => {
-                    let x = op.to_string().replace('_', ".");
-                    write!(
-                        f,
-                        "{} {}, {}",
-                        x,
-                        FloatRegister::from(*rd),
-                        FloatRegister::from(*rs)
-                    )
-                }
-                R::Cs => {
-                    write!(
-                        f,
-                        "c.{}.s {}, {}",
-                        FloatCond::try_from(*sa).unwrap(),
-                        FloatRegister::from(*rs),
-                        FloatRegister::from(*rt)
-                    )
-                }
-                R::Cd => {
-                    write!(
-                        f,
-                        "c.{}.d {}, {}",
-                        FloatCond::try_from(*sa).unwrap(),
-                        FloatRegister::from(*rs),
-                        FloatRegister::from(*rt)
-                    )
-                }
-                e => panic!("{:?} not implemented", e),
-            },
+            }
             Instruction::Vector {
                 op,
                 vd,
@@ -346,58 +717,84 @@ impl fmt::Display for Instruction {
                 vt,
                 e,
                 de,
-            } => match op {
-                V::Vrsq | V::Vrsqh | V::Vrsql | V::Vrcp | V::Vrcph | V::Vrcpl => {
-                    write!(f, "{:7} {}[{}] {}[{}]", op, vd, de, vt, e)
-                }
-                V::Vabs
-                | V::Vadd
-                | V::Vaddc
-                | V::Vand
-                | V::Vch
-                | V::Vcl
-                | V::Vcr
-                | V::Veq
-                | V::Vge
-                | V::Vlt
-                | V::Vmacf
-                | V::Vmacq
-                | V::Vmacu
-                | V::Vmadh
-                | V::Vmadl
-                | V::Vmadm
-                | V::Vmadn
-                | V::Vmov
-                | V::Vmrg
-                | V::Vmudh
-                | V::Vmudl
-                | V::Vmudm
-                | V::Vmudn
-                | V::Vmulf
-                | V::Vmulq
-                | V::Vmulu
-                | V::Vnand
-                | V::Vne
-                | V::Vnor
-                | V::Vnxor
-                | V::Vor
-                | V::Vrndn
-                | V::Vrndp
-                | V::Vsar
-                | V::Vsub
-                | V::Vsubc
-                | V::Vxor => {
-                    write!(f, "{:7} {} {} {}[{}]", op, vd, vs, vt, e)
-                }
-                V::Vnop => {
-                    write!(f, "{:7}", "Vnop")
+            } => {
+                let (vd, vs, vt) = (
+                    aliases.named_vreg(*vd),
+                    aliases.named_vreg(*vs),
+                    aliases.named_vreg(*vt),
+                );
+                match op {
+                    V::Vrsq | V::Vrsqh | V::Vrsql | V::Vrcp | V::Vrcph | V::Vrcpl => {
+                        write!(f, "{:7} {}[{}] {}[{}]", op, vd, de, vt, e)
+                    }
+                    V::Vabs
+                    | V::Vadd
+                    | V::Vaddc
+                    | V::Vand
+                    | V::Vch
+                    | V::Vcl
+                    | V::Vcr
+                    | V::Veq
+                    | V::Vge
+                    | V::Vlt
+                    | V::Vmacf
+                    | V::Vmacq
+                    | V::Vmacu
+                    | V::Vmadh
+                    | V::Vmadl
+                    | V::Vmadm
+                    | V::Vmadn
+                    | V::Vmov
+                    | V::Vmrg
+                    | V::Vmudh
+                    | V::Vmudl
+                    | V::Vmudm
+                    | V::Vmudn
+                    | V::Vmulf
+                    | V::Vmulq
+                    | V::Vmulu
+                    | V::Vnand
+                    | V::Vne
+                    | V::Vnor
+                    | V::Vnxor
+                    | V::Vor
+                    | V::Vrndn
+                    | V::Vrndp
+                    | V::Vsar
+                    | V::Vsub
+                    | V::Vsubc
+                    | V::Vxor => {
+                        write!(f, "{:7} {} {} {}[{}]", op, vd, vs, vt, e)
+                    }
+                    V::Vnop => write!(f, "{}", op),
                 }
-            },
+            }
+            Instruction::VectorMemory {
+                op,
+                vt,
+                e,
+                offset,
+                base,
+            } => {
+                let (vt, base) = (aliases.named_vreg(*vt), aliases.named_gpr(*base));
+                // The 7 bit offset is signed and scaled by the access size
+                let offset = ((*offset as i32) << 25 >> 25) * op.scale() as i32;
+                write!(
+                    f,
+                    "{:7} {}[{}], {:#x}({})",
+                    op,
+                    vt,
+                    e,
+                    Signed(offset as u16),
+                    base
+                )
+            }
         }
     }
 }
 
-#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+/// A general purpose register
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[strum(serialize_all = "snake_case")]
 pub enum Register {
     Zero,
@@ -435,10 +832,12 @@ pub enum Register {
 }
 
 impl Register {
+    /// The register used for fields an instruction does not encode
     pub fn null() -> Self {
         Register::Zero
     }
 
+    /// The register number as encoded in the instruction
     pub fn as_num(&self) -> u32 {
         *self as u32
     }
@@ -486,19 +885,79 @@ impl TryFrom<u32> for Register {
     }
 }
 
+impl FromStr for Register {
+    type Err = RegParseError;
+
+    fn from_str(reg: &str) -> Result<Self, Self::Err> {
+        let reg = reg.trim().trim_start_matches('$');
+
+        if let Ok(x) = reg.parse::<u32>() {
+            return Register::try_from(x);
+        }
+
+        match reg.to_lowercase().as_str() {
+            "zero" => Ok(Register::Zero),
+            "at" => Ok(Register::At),
+            "v0" => Ok(Register::V0),
+            "v1" => Ok(Register::V1),
+            "a0" => Ok(Register::A0),
+            "a1" => Ok(Register::A1),
+            "a2" => Ok(Register::A2),
+            "a3" => Ok(Register::A3),
+            "t0" => Ok(Register::T0),
+            "t1" => Ok(Register::T1),
+            "t2" => Ok(Register::T2),
+            "t3" => Ok(Register::T3),
+            "t4" => Ok(Register::T4),
+            "t5" => Ok(Register::T5),
+            "t6" => Ok(Register::T6),
+            "t7" => Ok(Register::T7),
+            "s0" => Ok(Register::S0),
+            "s1" => Ok(Register::S1),
+            "s2" => Ok(Register::S2),
+            "s3" => Ok(Register::S3),
+            "s4" => Ok(Register::S4),
+            "s5" => Ok(Register::S5),
+            "s6" => Ok(Register::S6),
+            "s7" => Ok(Register::S7),
+            "t8" => Ok(Register::T8),
+            "t9" => Ok(Register::T9),
+            "k0" => Ok(Register::K0),
+            "k1" => Ok(Register::K1),
+            "gp" => Ok(Register::Gp),
+            "sp" => Ok(Register::Sp),
+            "fp" | "s8" => Ok(Register::Fp),
+            "ra" => Ok(Register::Ra),
+            e => {
+                if let Some(Ok(x)) = e.strip_prefix('r').map(|x| x.parse::<u32>()) {
+                    return Register::try_from(x);
+                }
+                Err(RegParseError::RegParseError(e.to_string()))
+            }
+        }
+    }
+}
+
 impl From<FloatRegister> for Register {
     fn from(reg: FloatRegister) -> Self {
         Register::try_from(reg as u32).unwrap()
     }
 }
 
+impl From<VuRegister> for Register {
+    fn from(reg: VuRegister) -> Self {
+        Register::try_from(reg as u32).unwrap()
+    }
+}
+
 impl From<Cop0Register> for Register {
     fn from(reg: Cop0Register) -> Self {
         Register::try_from(reg as u32).unwrap()
     }
 }
 
-#[derive(Clone, Copy, Debug, Display, PartialEq, Eq)]
+/// A vector unit register
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord, Hash)]
 #[strum(serialize_all = "snake_case")]
 pub enum VuRegister {
     V0,
@@ -536,6 +995,7 @@ pub enum VuRegister {
 }
 
 impl VuRegister {
+    /// The register used for fields an instruction does not encode
     pub fn null() -> Self {
         VuRegister::V0
     }
@@ -583,7 +1043,8 @@ impl TryFrom<u32> for VuRegister {
     }
 }
 
-#[derive(Clone, Copy, Debug, Display)]
+/// A floating point register
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
 #[strum(serialize_all = "snake_case")]
 pub enum FloatRegister {
     Fv0,
@@ -662,6 +1123,29 @@ impl TryFrom<u32> for FloatRegister {
     }
 }
 
+impl From<Register> for VuRegister {
+    fn from(reg: Register) -> Self {
+        VuRegister::try_from(reg as u32).unwrap()
+    }
+}
+
+impl FromStr for VuRegister {
+    type Err = RegParseError;
+
+    fn from_str(reg: &str) -> Result<Self, Self::Err> {
+        let reg = reg.trim().trim_start_matches('$');
+
+        match reg
+            .strip_prefix('v')
+            .or_else(|| reg.strip_prefix('V'))
+            .map(|x| x.parse::<u32>())
+        {
+            Some(Ok(x)) => VuRegister::try_from(x),
+            _ => Err(RegParseError::RegParseError(reg.to_string())),
+        }
+    }
+}
+
 impl FromStr for FloatRegister {
     type Err = RegParseError;
 
@@ -721,7 +1205,55 @@ impl From<Register> for FloatRegister {
     }
 }
 
-#[derive(Clone, Copy, Debug, Display)]
+/// A vector unit control register, used by `cfc2` and `ctc2`
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "snake_case")]
+pub enum VuControlRegister {
+    Vco,
+    Vcc,
+    Vce,
+}
+
+impl TryFrom<u32> for VuControlRegister {
+    type Error = RegParseError;
+
+    fn try_from(reg: u32) -> Result<Self, Self::Error> {
+        match reg {
+            0 => Ok(VuControlRegister::Vco),
+            1 => Ok(VuControlRegister::Vcc),
+            2 => Ok(VuControlRegister::Vce),
+            e => Err(RegParseError::RegParseError(e.to_string())),
+        }
+    }
+}
+
+impl FromStr for VuControlRegister {
+    type Err = RegParseError;
+
+    fn from_str(reg: &str) -> Result<Self, Self::Err> {
+        let reg = reg.trim().trim_start_matches('$');
+
+        if let Ok(x) = reg.parse::<u32>() {
+            return VuControlRegister::try_from(x);
+        }
+
+        match reg.to_lowercase().as_str() {
+            "vco" => Ok(VuControlRegister::Vco),
+            "vcc" => Ok(VuControlRegister::Vcc),
+            "vce" => Ok(VuControlRegister::Vce),
+            e => Err(RegParseError::RegParseError(e.to_string())),
+        }
+    }
+}
+
+impl From<VuControlRegister> for Register {
+    fn from(reg: VuControlRegister) -> Self {
+        Register::try_from(reg as u32).unwrap()
+    }
+}
+
+/// A RSP coprocessor 0 register, used by `mfc0` and `mtc0`
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
 #[strum(serialize_all = "PascalCase")]
 pub enum Cop0Register {
     IDMemAddressForDMA,
@@ -768,13 +1300,39 @@ impl TryFrom<u32> for Cop0Register {
     }
 }
 
+impl FromStr for Cop0Register {
+    type Err = RegParseError;
+
+    fn from_str(reg: &str) -> Result<Self, Self::Err> {
+        let reg = reg.trim().trim_start_matches('$');
+
+        if let Ok(x) = reg.parse::<u32>() {
+            return Cop0Register::try_from(x);
+        }
+
+        if let Some(Ok(x)) = reg
+            .strip_prefix('c')
+            .or_else(|| reg.strip_prefix('C'))
+            .map(|x| x.parse::<u32>())
+        {
+            return Cop0Register::try_from(x);
+        }
+
+        (0..16)
+            .filter_map(|x| Cop0Register::try_from(x).ok())
+            .find(|x| x.to_string().eq_ignore_ascii_case(reg))
+            .ok_or_else(|| RegParseError::RegParseError(reg.to_string()))
+    }
+}
+
 impl From<Register> for Cop0Register {
     fn from(reg: Register) -> Self {
         Cop0Register::try_from(reg as u32).unwrap()
     }
 }
 
-#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq)]
+/// Opcodes of [`Instruction::Immediate`]
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq, Hash)]
 #[strum(ascii_case_insensitive)]
 #[strum(serialize_all = "snake_case")]
 pub enum ITypeOp {
@@ -821,7 +1379,6 @@ pub enum ITypeOp {
     Lui,
     Lw,
     Lwc1,
-    Lbv,
     Lwl,
     Lwr,
     Lwu,
@@ -879,7 +1436,8 @@ pub enum ITypeOp {
     Subiu,
 }
 
-#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq)]
+/// Opcodes of [`Instruction::Vector`]
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq, Hash)]
 #[strum(ascii_case_insensitive)]
 #[strum(serialize_all = "snake_case")]
 pub enum VTypeOp {
@@ -930,7 +1488,201 @@ pub enum VTypeOp {
     Vxor,
 }
 
-#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq)]
+/// Opcodes of [`Instruction::VectorMemory`]
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq, Hash)]
+#[strum(ascii_case_insensitive)]
+#[strum(serialize_all = "snake_case")]
+pub enum VMemTypeOp {
+    // LWC2
+    Lbv,
+    Lsv,
+    Llv,
+    Ldv,
+    Lqv,
+    Lrv,
+    Lpv,
+    Luv,
+    Lhv,
+    Lfv,
+    Ltv,
+    // SWC2
+    Sbv,
+    Ssv,
+    Slv,
+    Sdv,
+    Sqv,
+    Srv,
+    Spv,
+    Suv,
+    Shv,
+    Sfv,
+    Swv,
+    Stv,
+}
+
+impl ITypeOp {
+    /// Returns whether the opcode is a pseudoinstruction the assembler expands
+    pub fn is_pseudo(&self) -> bool {
+        matches!(
+            self,
+            ITypeOp::B
+                | ITypeOp::Bal
+                | ITypeOp::Beqz
+                | ITypeOp::Bnez
+                | ITypeOp::Beqzl
+                | ITypeOp::Bnezl
+                | ITypeOp::Bge
+                | ITypeOp::Bgt
+                | ITypeOp::Ble
+                | ITypeOp::Blt
+                | ITypeOp::Bgeu
+                | ITypeOp::Bgtu
+                | ITypeOp::Bleu
+                | ITypeOp::Bltu
+                | ITypeOp::Bgel
+                | ITypeOp::Bgtl
+                | ITypeOp::Blel
+                | ITypeOp::Bltl
+                | ITypeOp::Bgeul
+                | ITypeOp::Bgtul
+                | ITypeOp::Bleul
+                | ITypeOp::Bltul
+                | ITypeOp::Dli
+                | ITypeOp::Dsubi
+                | ITypeOp::Dsubiu
+                | ITypeOp::Lli
+                | ITypeOp::Li
+                | ITypeOp::Subi
+                | ITypeOp::Subiu
+        )
+    }
+
+    /// Returns whether the immediate is a branch offset
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            ITypeOp::Bc0f
+                | ITypeOp::Bc0fl
+                | ITypeOp::Bc0t
+                | ITypeOp::Bc0tl
+                | ITypeOp::Bc1f
+                | ITypeOp::Bc1fl
+                | ITypeOp::Bc1t
+                | ITypeOp::Bc1tl
+                | ITypeOp::Beq
+                | ITypeOp::Beql
+                | ITypeOp::Bgez
+                | ITypeOp::Bgezal
+                | ITypeOp::Bgezall
+                | ITypeOp::Bgezl
+                | ITypeOp::Bgtz
+                | ITypeOp::Bgtzl
+                | ITypeOp::Blez
+                | ITypeOp::Blezl
+                | ITypeOp::Bltz
+                | ITypeOp::Bltzal
+                | ITypeOp::Bltzall
+                | ITypeOp::Bltzl
+                | ITypeOp::Bne
+                | ITypeOp::Bnel
+                | ITypeOp::B
+                | ITypeOp::Bal
+                | ITypeOp::Beqz
+                | ITypeOp::Bnez
+                | ITypeOp::Beqzl
+                | ITypeOp::Bnezl
+                | ITypeOp::Bge
+                | ITypeOp::Bgt
+                | ITypeOp::Ble
+                | ITypeOp::Blt
+                | ITypeOp::Bgeu
+                | ITypeOp::Bgtu
+                | ITypeOp::Bleu
+                | ITypeOp::Bltu
+                | ITypeOp::Bgel
+                | ITypeOp::Bgtl
+                | ITypeOp::Blel
+                | ITypeOp::Bltl
+                | ITypeOp::Bgeul
+                | ITypeOp::Bgtul
+                | ITypeOp::Bleul
+                | ITypeOp::Bltul
+        )
+    }
+}
+
+impl RTypeOp {
+    /// Returns whether the opcode is a pseudoinstruction
+    pub fn is_pseudo(&self) -> bool {
+        matches!(
+            self,
+            RTypeOp::Abs
+                | RTypeOp::Clear
+                | RTypeOp::Dabs
+                | RTypeOp::Dmove
+                | RTypeOp::Dmul
+                | RTypeOp::Dmulu
+                | RTypeOp::Dmulo
+                | RTypeOp::Dmulou
+                | RTypeOp::Dneg
+                | RTypeOp::Dnegu
+                | RTypeOp::Drem
+                | RTypeOp::Dremu
+                | RTypeOp::Drol
+                | RTypeOp::Dror
+                | RTypeOp::Move
+                | RTypeOp::Mul
+                | RTypeOp::Mulu
+                | RTypeOp::Mulo
+                | RTypeOp::Mulou
+                | RTypeOp::Neg
+                | RTypeOp::Negu
+                | RTypeOp::Nop
+                | RTypeOp::Not
+                | RTypeOp::Rem
+                | RTypeOp::Remu
+                | RTypeOp::Seq
+                | RTypeOp::Sge
+                | RTypeOp::Sgeu
+                | RTypeOp::Sgt
+                | RTypeOp::Sgtu
+                | RTypeOp::Sle
+                | RTypeOp::Sleu
+                | RTypeOp::Sne
+        )
+    }
+}
+
+impl VMemTypeOp {
+    /// Number of bytes the encoded offset is scaled by
+    pub fn scale(&self) -> u32 {
+        match self {
+            VMemTypeOp::Lbv | VMemTypeOp::Sbv => 1,
+            VMemTypeOp::Lsv | VMemTypeOp::Ssv => 2,
+            VMemTypeOp::Llv | VMemTypeOp::Slv => 4,
+            VMemTypeOp::Ldv
+            | VMemTypeOp::Sdv
+            | VMemTypeOp::Lpv
+            | VMemTypeOp::Spv
+            | VMemTypeOp::Luv
+            | VMemTypeOp::Suv => 8,
+            VMemTypeOp::Lqv
+            | VMemTypeOp::Sqv
+            | VMemTypeOp::Lrv
+            | VMemTypeOp::Srv
+            | VMemTypeOp::Lhv
+            | VMemTypeOp::Shv
+            | VMemTypeOp::Lfv
+            | VMemTypeOp::Sfv
+            | VMemTypeOp::Swv
+            | VMemTypeOp::Ltv
+            | VMemTypeOp::Stv => 16,
+        }
+    }
+}
+
+/// Opcodes of [`Instruction::Jump`]
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq, Hash)]
 #[strum(ascii_case_insensitive)]
 #[strum(serialize_all = "snake_case")]
 pub enum JTypeOp {
@@ -938,7 +1690,8 @@ pub enum JTypeOp {
     Jal,
 }
 
-#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq)]
+/// Opcodes of [`Instruction::Register`]
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq, Hash)]
 #[strum(ascii_case_insensitive)]
 #[strum(serialize_all = "snake_case")]
 pub enum RTypeOp {
@@ -968,8 +1721,10 @@ pub enum RTypeOp {
     CeilWD,
     Cfc0,
     Cfc1,
+    Cfc2,
     Ctc0,
     Ctc1,
+    Ctc2,
     #[strum(to_string = "cvt.d.s")]
     CvtDS,
     #[strum(to_string = "cvt.d.w")]
@@ -1030,6 +1785,7 @@ pub enum RTypeOp {
     Jr,
     Mfc0,
     Mfc1,
+    Mfc2,
     Mfhi,
     Mflo,
     #[strum(to_string = "mov.s")]
@@ -1038,6 +1794,7 @@ pub enum RTypeOp {
     MovD,
     Mtc0,
     Mtc1,
+    Mtc2,
     Mthi,
     Mtlo,
     #[strum(to_string = "mul.s")]
@@ -1135,7 +1892,8 @@ pub enum RTypeOp {
     Sne,
 }
 
-#[derive(Clone, Copy, Debug, Display, EnumString)]
+/// The condition of a `c.cond.fmt` float compare
+#[derive(Clone, Copy, Debug, Display, EnumString, PartialEq, Eq, Hash)]
 #[strum(ascii_case_insensitive)]
 #[strum(serialize_all = "snake_case")]
 pub enum FloatCond {
@@ -0,0 +1,377 @@
+use crate::parser::{split_labels, split_list, split_statement, unquoted};
+
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// The assembler a program is written for, which decides its comments,
+/// register prefixes, directive names and vector element syntax
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Dialect {
+    /// The syntax of this crate, with `#`, `;` and `//` comments and registers
+    /// written with or without `$`
+    #[default]
+    Mipsasm,
+    /// armips, with `;`, `//` and `/* */` comments, `@@` local labels, macro
+    /// parameters used without `\` and `vt[N]` selecting halfword N
+    Armips,
+    /// bass, with `//` and `/* */` comments, `;` between statements,
+    /// directives without `.`, registers without `$`, which starts a
+    /// hexadecimal number, and `vt[eN]` encoding the element as N
+    Bass,
+    /// GNU as with libdragon's `rsp.inc`, with `#` and `/* */` comments, the
+    /// C preprocessor lines used for constants, aliases, includes and
+    /// conditions, `;` between statements and vector registers written with `$`
+    Gnu,
+}
+
+// How a directive of a dialect is written in the syntax of this crate
+enum Translation {
+    Rename(&'static str),
+    // Directives without an effect on the program, such as symbol visibility
+    Drop,
+}
+
+impl Dialect {
+    /// Returns whether `reg` is written with the register prefix of the dialect
+    pub fn register_prefix(&self, reg: &str) -> bool {
+        match self {
+            Dialect::Bass => !reg.starts_with('$'),
+            _ => true,
+        }
+    }
+
+    /// Returns whether vector register `reg` is written with the register
+    /// prefix of the dialect
+    pub fn vu_register_prefix(&self, reg: &str) -> bool {
+        match self {
+            Dialect::Gnu => reg.starts_with('$'),
+            _ => self.register_prefix(reg),
+        }
+    }
+
+    /// The number of a COP0 register named by `rsp.inc`, as `COP0_*` or `$cN`
+    pub fn cop0(&self, reg: &str) -> Option<u32> {
+        const NAMES: [&str; 16] = [
+            "COP0_DMA_SPADDR",
+            "COP0_DMA_RAMADDR",
+            "COP0_DMA_READ",
+            "COP0_DMA_WRITE",
+            "COP0_SP_STATUS",
+            "COP0_DMA_FULL",
+            "COP0_DMA_BUSY",
+            "COP0_SEMAPHORE",
+            "COP0_DP_START",
+            "COP0_DP_END",
+            "COP0_DP_CURRENT",
+            "COP0_DP_STATUS",
+            "COP0_DP_CLOCK",
+            "COP0_DP_BUSY",
+            "COP0_DP_PIPE_BUSY",
+            "COP0_DP_TMEM_BUSY",
+        ];
+        if *self != Dialect::Gnu {
+            return None;
+        }
+        match reg.strip_prefix("$c") {
+            Some(n) => n.parse().ok().filter(|&n| n < 16),
+            None => NAMES.iter().position(|x| *x == reg).map(|x| x as u32),
+        }
+    }
+
+    fn comments(&self) -> &'static [&'static str] {
+        match self {
+            Dialect::Mipsasm => &["#", ";", "//"],
+            Dialect::Armips => &[";", "//"],
+            Dialect::Bass => &["//"],
+            Dialect::Gnu => &["#", "//"],
+        }
+    }
+
+    fn separator(&self) -> Option<char> {
+        match self {
+            Dialect::Bass | Dialect::Gnu => Some(';'),
+            Dialect::Mipsasm | Dialect::Armips => None,
+        }
+    }
+
+    fn translation(&self, directive: &str) -> Option<Translation> {
+        use Translation::*;
+
+        let directive = directive.to_ascii_lowercase();
+        let translation = match (self, directive.as_str()) {
+            (Dialect::Armips, ".db") => Rename(".byte"),
+            (Dialect::Armips, ".dh" | ".halfword") => Rename(".half"),
+            (Dialect::Armips, ".dw") => Rename(".word"),
+            (Dialect::Armips, ".dd" | ".doubleword") => Rename(".dword"),
+            (Dialect::Armips, ".fill") => Rename(".space"),
+            (Dialect::Armips, ".align") => Rename(".balign"),
+            (Dialect::Armips, ".import") => Rename(".incbin"),
+            (Dialect::Armips, ".endmacro") => Rename(".endm"),
+            (Dialect::Armips, ".definelabel") => Rename(".equ"),
+            (Dialect::Armips, ".function") => Rename(".func"),
+            (Dialect::Armips, ".endfunction") => Rename(".endfunc"),
+            (Dialect::Armips, ".rsp") => Drop,
+            (Dialect::Bass, "db") => Rename(".byte"),
+            (Dialect::Bass, "dw") => Rename(".half"),
+            (Dialect::Bass, "dd") => Rename(".word"),
+            (Dialect::Bass, "dq") => Rename(".dword"),
+            (Dialect::Bass, "fill") => Rename(".space"),
+            (Dialect::Bass, "align") => Rename(".balign"),
+            (Dialect::Bass, "insert") => Rename(".incbin"),
+            (Dialect::Bass, "include") => Rename(".include"),
+            (Dialect::Bass, "arch") => Drop,
+            (Dialect::Gnu, ".asciz" | ".string") => Rename(".asciiz"),
+            (Dialect::Gnu, ".hword" | ".short") => Rename(".half"),
+            (Dialect::Gnu, ".long" | ".int") => Rename(".word"),
+            (Dialect::Gnu, ".quad") => Rename(".dword"),
+            (Dialect::Gnu, ".skip") => Rename(".space"),
+            (Dialect::Gnu, ".p2align") => Rename(".align"),
+            (
+                Dialect::Gnu,
+                ".globl" | ".global" | ".local" | ".hidden" | ".type" | ".size" | ".ent" | ".end",
+            ) => Drop,
+            _ => return None,
+        };
+        Some(translation)
+    }
+}
+
+/// Reads the lines of a file written in a dialect as statements in the
+/// syntax of this crate
+pub struct Lexer {
+    dialect: Dialect,
+    // Whether a `/* */` comment continues on the next line
+    comment: bool,
+}
+
+impl Lexer {
+    pub fn new(dialect: Dialect) -> Lexer {
+        Lexer {
+            dialect,
+            comment: false,
+        }
+    }
+
+    /// The statements on `line`, with at least one for every line
+    pub fn statements<'b>(&mut self, line: &'b str) -> Vec<Cow<'b, str>> {
+        if self.dialect == Dialect::Mipsasm {
+            return vec![Cow::Borrowed(line)];
+        }
+        let text = self.strip_comments(line);
+        if self.dialect == Dialect::Gnu && text.trim_start().starts_with('#') {
+            return vec![preprocessor(text.trim()).map_or(Cow::Borrowed(""), Cow::Owned)];
+        }
+        let statements = match (&text, self.dialect.separator()) {
+            (Cow::Borrowed(text), Some(c)) => {
+                split(text, c).into_iter().map(Cow::Borrowed).collect()
+            }
+            (Cow::Owned(text), Some(c)) => split(text, c)
+                .into_iter()
+                .map(|x| Cow::Owned(x.to_string()))
+                .collect(),
+            (_, None) => vec![text],
+        };
+        statements.into_iter().map(|x| self.statement(x)).collect()
+    }
+
+    // Removes comments, `#` lines of the C preprocessor are kept for GNU as
+    fn strip_comments<'b>(&mut self, line: &'b str) -> Cow<'b, str> {
+        let gnu_directive =
+            self.dialect == Dialect::Gnu && !self.comment && preprocessor(line.trim()).is_some();
+        let comments = self
+            .dialect
+            .comments()
+            .iter()
+            .filter(|&&x| !(gnu_directive && x == "#"));
+
+        // Byte ranges of the line outside of comments
+        let mut kept = vec![];
+        let mut start = 0;
+        let mut quoted = false;
+        let mut chars = line.char_indices();
+        while let Some((i, c)) = chars.next() {
+            let rest = &line[i..];
+            if self.comment {
+                if rest.starts_with("*/") {
+                    chars.next();
+                    self.comment = false;
+                    start = i + 2;
+                }
+            } else if quoted {
+                match c {
+                    '\\' => {
+                        chars.next();
+                    }
+                    '"' => quoted = false,
+                    _ => {}
+                }
+            } else if c == '"' {
+                quoted = true;
+            } else if rest.starts_with("/*") && self.dialect != Dialect::Mipsasm {
+                kept.push(start..i);
+                chars.next();
+                self.comment = true;
+            } else if comments.clone().any(|x| rest.starts_with(x)) {
+                kept.push(start..i);
+                start = line.len();
+                break;
+            }
+        }
+        if !self.comment && start < line.len() {
+            kept.push(start..line.len());
+        }
+
+        match kept.as_slice() {
+            [] => Cow::Borrowed(""),
+            [range] => Cow::Borrowed(&line[range.clone()]),
+            ranges => Cow::Owned(
+                ranges
+                    .iter()
+                    .map(|x| &line[x.clone()])
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+        }
+    }
+
+    // Rewrites labels, numbers and directives of one statement
+    fn statement<'b>(&self, stmt: Cow<'b, str>) -> Cow<'b, str> {
+        let stmt = match self.dialect {
+            Dialect::Armips if stmt.contains("@@") => Cow::Owned(replace(&stmt, |rest| {
+                rest.strip_prefix("@@").map(|x| (".", x))
+            })),
+            Dialect::Bass if stmt.contains('$') => Cow::Owned(replace(&stmt, |rest| {
+                let hex = rest.strip_prefix('$')?;
+                hex.starts_with(|c: char| c.is_ascii_hexdigit())
+                    .then_some(("0x", hex))
+            })),
+            _ => stmt,
+        };
+
+        let (_, rest) = split_labels(&stmt);
+        let labels = &stmt[..rest.as_ptr() as usize - stmt.as_ptr() as usize];
+        let (mnemonic, ops) = split_statement(rest);
+        let translated = match (self.dialect, self.dialect.translation(mnemonic)) {
+            (_, Some(Translation::Rename(directive))) => match split_list(ops).as_slice() {
+                // `insert name, "file"` labels the data
+                [name, path, ..]
+                    if mnemonic.eq_ignore_ascii_case("insert") && !name.starts_with('"') =>
+                {
+                    let ops = &ops[path.as_ptr() as usize - ops.as_ptr() as usize..];
+                    format!("{}{}: {} {}", labels, name, directive, ops)
+                }
+                _ => format!("{}{} {}", labels, directive, ops),
+            },
+            (_, Some(Translation::Drop)) => labels.to_string(),
+            // `.set noreorder` and the other assembler options
+            (Dialect::Gnu, None)
+                if mnemonic.eq_ignore_ascii_case(".set") && split_list(ops).len() == 1 =>
+            {
+                labels.to_string()
+            }
+            (Dialect::Gnu, None) if mnemonic.eq_ignore_ascii_case(".section") => {
+                let section = split_list(ops).first().copied().unwrap_or_default();
+                match section {
+                    ".text" => format!("{}.text", labels),
+                    ".data" | ".rodata" | ".bss" => format!("{}.data", labels),
+                    _ => return stmt,
+                }
+            }
+            // `name equ value`
+            (Dialect::Armips, None) if split_statement(ops).0.eq_ignore_ascii_case("equ") => {
+                format!("{}.equ {}, {}", labels, mnemonic, split_statement(ops).1)
+            }
+            // `constant name = value` or `constant name(value)`
+            (Dialect::Bass, None) if mnemonic.eq_ignore_ascii_case("constant") => {
+                let definition = match ops.split_once('(') {
+                    Some((name, value)) if !ops.contains('=') => {
+                        format!(
+                            "{} = {}",
+                            name.trim(),
+                            value.strip_suffix(')').unwrap_or(value)
+                        )
+                    }
+                    _ => ops.to_string(),
+                };
+                format!("{}{}", labels, definition)
+            }
+            _ => return stmt,
+        };
+        Cow::Owned(translated)
+    }
+}
+
+// The statement for a line of the C preprocessor, `None` for comments and the
+// preprocessor lines without one
+fn preprocessor(line: &str) -> Option<String> {
+    let (directive, ops) = split_statement(line.strip_prefix('#')?.trim_start());
+    let statement = match directive {
+        "define" => {
+            let (name, value) = split_statement(ops);
+            // Function-like macros have no equivalent
+            if name.contains('(') {
+                return None;
+            }
+            match value {
+                "" => format!(".equ {}, 1", name),
+                value if value.starts_with('$') => format!(".alias {}, {}", name, value),
+                value => format!(".equ {}, {}", name, value),
+            }
+        }
+        "include" => {
+            let path = ops.trim_matches(|c| c == '<' || c == '>' || c == '"');
+            // The syntax of `rsp.inc` is built in
+            if path == "rsp.inc" {
+                return Some(String::new());
+            }
+            format!(".include \"{}\"", path)
+        }
+        "if" => format!(".if {}", ops),
+        "ifdef" => format!(".ifdef {}", ops),
+        "ifndef" => format!(".ifndef {}", ops),
+        "elif" => format!(".elseif {}", ops),
+        "else" => ".else".to_string(),
+        "endif" => ".endif".to_string(),
+        _ => return None,
+    };
+    Some(statement)
+}
+
+// Splits `text` at the unquoted `separator`s
+fn split(text: &str, separator: char) -> Vec<&str> {
+    let mut parts = vec![];
+    let mut start = 0;
+    for (i, _) in unquoted(text).filter(|&(_, c)| c == separator) {
+        parts.push(&text[start..i]);
+        start = i + separator.len_utf8();
+    }
+    parts.push(&text[start..]);
+    parts
+}
+
+// Replaces the unquoted text `f` matches at the start of a word, `f` returns
+// the replacement and the text after the match
+fn replace(text: &str, f: impl Fn(&str) -> Option<(&'static str, &str)>) -> String {
+    let mut s = String::new();
+    let mut end = 0;
+    let mut word = false;
+    for (i, c) in unquoted(text) {
+        if i < end {
+            continue;
+        }
+        let start = !word;
+        word = c.is_ascii_alphanumeric() || c == '_';
+        if let Some((replacement, rest)) = start.then(|| f(&text[i..])).flatten() {
+            s.push_str(&text[end..i]);
+            s.push_str(replacement);
+            end = text.len() - rest.len();
+        }
+    }
+    s.push_str(&text[end..]);
+    s
+}
@@ -1,7 +1,12 @@
+use crate::parser::{Element, Section};
+use crate::target::Target;
+
 use alloc::{
+    boxed::Box,
     fmt::{self, Write},
     format,
     string::{String, ToString},
+    vec::Vec,
 };
 use core::cmp;
 
@@ -9,37 +14,53 @@ use core::cmp;
 macro_rules! error {
     ($self:ident, MultipleLabelDefinition, $label:expr, $first:expr) => {
         ParserError::MultipleLabelDefinition {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             label: $label.to_string(),
-            first: Line::new($first + 1, $self.input.get($first).unwrap().to_string()),
+            first: $self.line($first + 1),
+        }
+    };
+    ($self:ident, MultipleConstantDefinition, $constant:expr, $first:expr) => {
+        ParserError::MultipleConstantDefinition {
+            line: $self.line($self.line_num),
+            constant: $constant.to_string(),
+            first: $self.line($first + 1),
+        }
+    };
+    ($self:ident, MultipleAliasDefinition, $name:expr, $first:expr) => {{
+        let (line, first) = ($self.line_num - 1, $first);
+        ParserError::MultipleAliasDefinition {
+            line: $self.line(line.max(first) + 1),
+            name: $name.to_string(),
+            first: $self.line(line.min(first) + 1),
+        }
+    }};
+    ($self:ident, InvalidAliasName, $name:expr) => {
+        ParserError::InvalidAliasName {
+            line: $self.line($self.line_num),
+            name: $name.to_string(),
+        }
+    };
+    ($self:ident, InvalidElement, $e:expr, $element:expr) => {
+        ParserError::InvalidElement {
+            line: $self.line($self.line_num),
+            e: $e.to_string(),
+            element: $element,
         }
     };
     ($self:ident, InvalidLabel, $label:expr) => {
         ParserError::InvalidLabel {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             label: $label.to_string(),
         }
     };
     ($self:ident, InvalidInstruction) => {
         ParserError::InvalidInstruction {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
         }
     };
     ($self:ident, InvalidOperandCount, $ops:expr, $expected:expr, $found:expr) => {
         ParserError::InvalidOperandCount {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             ops: $ops.to_string(),
             expected: $expected,
             found: $found,
@@ -47,122 +68,227 @@ macro_rules! error {
     };
     ($self:ident, InvalidOpcode, $opcode:expr) => {
         ParserError::InvalidOpcode {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             opcode: $opcode.to_string(),
         }
     };
     ($self:ident, InvalidRegister, $register:expr) => {
         ParserError::InvalidRegister {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             register: $register.to_string(),
         }
     };
     ($self:ident, InvalidTargetAddress, $target:expr) => {
         ParserError::InvalidTargetAddress {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             address: $target.to_string(),
         }
     };
     ($self:ident, InvalidImmediate, $immediate:expr) => {
         ParserError::InvalidImmediate {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             immediate: $immediate.to_string(),
         }
     };
+    ($self:ident, InvalidString, $string:expr) => {
+        ParserError::InvalidString {
+            line: $self.line($self.line_num),
+            string: $string.to_string(),
+        }
+    };
+    ($self:ident, UnalignedInstruction, $opcode:expr, $addr:expr) => {
+        ParserError::UnalignedInstruction {
+            line: $self.line($self.line_num),
+            opcode: $opcode.to_string(),
+            addr: $addr,
+        }
+    };
     ($self:ident, InvalidFloatCond, $cond:expr) => {
         ParserError::InvalidFloatCond {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             cond: $cond.to_string(),
         }
     };
     ($self:ident, BranchOutOfBounds, $line:expr, $target:expr, $bounds:expr) => {
         ParserError::BranchOutOfBounds {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($self.line_num),
             branch: $target,
             bounds: $bounds,
         }
     };
     ($self:ident, LocalLabelOutOfScope, $line_num:expr, $label:expr) => {
         ParserError::LocalLabelOutOfScope {
-            line: Line::new(
-                $line_num,
-                $self.input.get($line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($line_num),
             label: $label.to_string(),
         }
     };
     ($self:ident, UndefinedLabel, $line_num:expr, $label:expr) => {
         ParserError::UndefinedLabel {
-            line: Line::new(
-                $line_num,
-                $self.input.get($line_num - 1).unwrap().to_string(),
-            ),
+            line: $self.line($line_num),
             label: $label.to_string(),
         }
     };
+    ($self:ident, InvalidConstant, $constant:expr) => {
+        ParserError::InvalidConstant {
+            line: $self.line($self.line_num),
+            constant: $constant.to_string(),
+        }
+    };
+    ($self:ident, SectionOverflow, $opcode:expr, $section:expr, $symbol:expr, $over:expr) => {
+        ParserError::SectionOverflow {
+            line: $self.line($self.line_num),
+            opcode: $opcode.to_string(),
+            section: $section,
+            symbol: $symbol.map(|x: &str| x.to_string()),
+            over: $over,
+        }
+    };
+    ($self:ident, UnsupportedInstruction, $opcode:expr, $target:expr) => {
+        ParserError::UnsupportedInstruction {
+            line: $self.line($self.line_num),
+            opcode: $opcode.to_string(),
+            target: $target,
+        }
+    };
 }
 
 #[macro_export]
 macro_rules! warning {
-    ($self:ident, InvalidInstructionInDelaySlot) => {
+    ($self:ident, InvalidInstructionInDelaySlot, $line_num:expr) => {
         ParserWarning::InvalidInstructionInDelaySlot {
-            delay_slot_inst: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
-            line: Line::new(
-                $self.line_num - 1,
-                $self.input.get($self.line_num - 2).unwrap().to_string(),
-            ),
+            delay_slot_inst: $self.line($self.line_num),
+            line: $self.line($line_num),
         }
     };
     ($self:ident, UnalignedBranch, $target:expr) => {
         ParserWarning::UnalignedBranch {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
-            offset: $target,
+            line: $self.line($self.line_num),
+            offset: $target.to_string(),
         }
     };
     ($self:ident, UnalignedJump, $target:expr) => {
         ParserWarning::UnalignedJump {
-            line: Line::new(
-                $self.line_num,
-                $self.input.get($self.line_num - 1).unwrap().to_string(),
-            ),
-            target: $target,
+            line: $self.line($self.line_num),
+            target: $target.to_string(),
         }
     };
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct Line {
     num: usize,
     content: String,
+    // Where the line comes from, if it is not written in the input itself
+    origin: Option<Box<Origin>>,
+}
+
+#[derive(Clone, Debug)]
+struct Origin {
+    // File the line is in, `None` for the input
+    file: Option<String>,
+    // Macro invocations and includes the line is expanded from, innermost first
+    expansions: Vec<Expansion>,
 }
 
 impl Line {
-    pub fn new(num: usize, content: String) -> Self {
-        Self { num, content }
+    pub fn new(
+        num: usize,
+        content: String,
+        file: Option<String>,
+        expansions: Vec<Expansion>,
+    ) -> Self {
+        let origin = match (&file, expansions.is_empty()) {
+            (None, true) => None,
+            _ => Some(Box::new(Origin { file, expansions })),
+        };
+        Self {
+            num,
+            content,
+            origin,
+        }
+    }
+
+    // Names the file the line is in and points at the macro invocations and
+    // includes it is expanded from, skipping the recursive ones between the
+    // innermost and outermost
+    fn write_origin(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Some(Origin { file, expansions }) = self.origin.as_deref() else {
+            return Ok(());
+        };
+        if let Some(file) = file {
+            writeln!(f, "\x1b[94m  = \x1b[0mnote: in `{}`", file)?;
+        }
+        let mut skipped = false;
+        for (i, expansion) in expansions.iter().enumerate() {
+            let recursive = |other: Option<&Expansion>| {
+                other.is_some_and(|x| x.include == expansion.include && x.name == expansion.name)
+            };
+            if i > 0 && recursive(expansions.get(i - 1)) && recursive(expansions.get(i + 1)) {
+                if !skipped {
+                    writeln!(f, "\x1b[94m...\x1b[0m")?;
+                    skipped = true;
+                }
+                continue;
+            }
+            skipped = false;
+            let Expansion {
+                name,
+                include,
+                file,
+                num,
+                content,
+            } = expansion;
+            if let Some(file) = file {
+                writeln!(f, "\x1b[94m  -->\x1b[0m {}:{}", file, num)?;
+            }
+            let msg = match include {
+                true => "included here".to_string(),
+                false => format!("in this expansion of macro `{}`", name),
+            };
+            writeln!(
+                f,
+                "{}",
+                fmt_line(
+                    *num,
+                    content,
+                    num.to_string().len(),
+                    true,
+                    &msg,
+                    false,
+                    name
+                )
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// A macro invocation or `.include` lines are expanded from
+#[derive(Clone, Debug)]
+pub struct Expansion {
+    // Name of the macro or path of the included file
+    name: String,
+    include: bool,
+    // File the invocation or `.include` is in, `None` for the input
+    file: Option<String>,
+    num: usize,
+    content: String,
+}
+
+impl Expansion {
+    pub fn new(name: String, include: bool, line: &Line) -> Self {
+        Self {
+            name,
+            include,
+            file: line.origin.as_ref().and_then(|x| x.file.clone()),
+            num: line.num,
+            content: line.content.clone(),
+        }
+    }
+
+    /// Whether the line is expanded from an `.include` rather than a macro
+    pub fn is_include(&self) -> bool {
+        self.include
     }
 }
 
@@ -173,6 +299,11 @@ pub enum ParserError {
         label: String,
         first: Line,
     },
+    MultipleConstantDefinition {
+        line: Line,
+        constant: String,
+        first: Line,
+    },
     InvalidLabel {
         line: Line,
         label: String,
@@ -202,6 +333,15 @@ pub enum ParserError {
         line: Line,
         immediate: String,
     },
+    InvalidString {
+        line: Line,
+        string: String,
+    },
+    UnalignedInstruction {
+        line: Line,
+        opcode: String,
+        addr: u32,
+    },
     InvalidFloatCond {
         line: Line,
         cond: String,
@@ -219,25 +359,181 @@ pub enum ParserError {
         line: Line,
         label: String,
     },
+    InvalidConstant {
+        line: Line,
+        constant: String,
+    },
+    UnsupportedInstruction {
+        line: Line,
+        opcode: String,
+        target: Target,
+    },
+    SectionOverflow {
+        line: Line,
+        opcode: String,
+        section: Section,
+        symbol: Option<String>,
+        over: u32,
+    },
+    MultipleMacroDefinition {
+        line: Line,
+        name: String,
+        first: Line,
+    },
+    InvalidMacroName {
+        line: Line,
+        name: String,
+    },
+    InvalidMacroParameter {
+        line: Line,
+        param: String,
+    },
+    InvalidMacroArgument {
+        line: Line,
+        name: String,
+        arg: String,
+    },
+    MissingMacroArgument {
+        line: Line,
+        name: String,
+        param: String,
+    },
+    UnterminatedMacro {
+        line: Line,
+        name: String,
+    },
+    MacroRecursion {
+        line: Line,
+        name: String,
+        depth: usize,
+    },
+    InvalidInclude {
+        line: Line,
+        path: String,
+        reason: String,
+    },
+    InvalidCondition {
+        line: Line,
+        condition: String,
+    },
+    UnexpectedConditional {
+        line: Line,
+        directive: String,
+        after_else: bool,
+    },
+    UnterminatedConditional {
+        line: Line,
+        directive: String,
+    },
+    MultipleAliasDefinition {
+        line: Line,
+        name: String,
+        first: Line,
+    },
+    InvalidAliasName {
+        line: Line,
+        name: String,
+    },
+    InvalidElement {
+        line: Line,
+        e: String,
+        element: Element,
+    },
+    DeniedWarning(ParserWarning),
 }
 
-impl fmt::Display for ParserError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl ParserError {
+    fn line(&self) -> &Line {
+        match self {
+            Self::MultipleLabelDefinition { line, .. }
+            | Self::MultipleConstantDefinition { line, .. }
+            | Self::InvalidLabel { line, .. }
+            | Self::InvalidInstruction { line }
+            | Self::InvalidOperandCount { line, .. }
+            | Self::InvalidOpcode { line, .. }
+            | Self::InvalidRegister { line, .. }
+            | Self::InvalidTargetAddress { line, .. }
+            | Self::InvalidImmediate { line, .. }
+            | Self::InvalidString { line, .. }
+            | Self::UnalignedInstruction { line, .. }
+            | Self::InvalidFloatCond { line, .. }
+            | Self::BranchOutOfBounds { line, .. }
+            | Self::LocalLabelOutOfScope { line, .. }
+            | Self::UndefinedLabel { line, .. }
+            | Self::InvalidConstant { line, .. }
+            | Self::UnsupportedInstruction { line, .. }
+            | Self::SectionOverflow { line, .. }
+            | Self::MultipleMacroDefinition { line, .. }
+            | Self::InvalidMacroName { line, .. }
+            | Self::InvalidMacroParameter { line, .. }
+            | Self::InvalidMacroArgument { line, .. }
+            | Self::MissingMacroArgument { line, .. }
+            | Self::UnterminatedMacro { line, .. }
+            | Self::MacroRecursion { line, .. }
+            | Self::InvalidInclude { line, .. }
+            | Self::InvalidCondition { line, .. }
+            | Self::UnexpectedConditional { line, .. }
+            | Self::UnterminatedConditional { line, .. }
+            | Self::MultipleAliasDefinition { line, .. }
+            | Self::InvalidAliasName { line, .. }
+            | Self::InvalidElement { line, .. } => line,
+            Self::DeniedWarning(warning) => warning.line(),
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
             Self::MultipleLabelDefinition {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 label,
                 first:
                     Line {
                         num: first_num,
                         content: first_content,
+                        ..
+                    },
+            }
+            | Self::MultipleConstantDefinition {
+                line: Line { num, content, .. },
+                constant: label,
+                first:
+                    Line {
+                        num: first_num,
+                        content: first_content,
+                        ..
+                    },
+            }
+            | Self::MultipleMacroDefinition {
+                line: Line { num, content, .. },
+                name: label,
+                first:
+                    Line {
+                        num: first_num,
+                        content: first_content,
+                        ..
+                    },
+            }
+            | Self::MultipleAliasDefinition {
+                line: Line { num, content, .. },
+                name: label,
+                first:
+                    Line {
+                        num: first_num,
+                        content: first_content,
+                        ..
                     },
             } => {
                 let margin = cmp::max(num.to_string().len(), first_num.to_string().len());
+                let kind = match self {
+                    Self::MultipleLabelDefinition { .. } => "label",
+                    Self::MultipleConstantDefinition { .. } => "constant",
+                    Self::MultipleMacroDefinition { .. } => "macro",
+                    _ => "alias",
+                };
                 writeln!(
                     f,
-                    "\x1b[91merror\x1b[0m: label `{}` defined multiple times",
-                    label
+                    "\x1b[91merror\x1b[0m: {} `{}` defined multiple times",
+                    kind, label
                 )?;
                 writeln!(
                     f,
@@ -260,7 +556,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidLabel {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 label,
             } => {
                 let margin = num.to_string().len();
@@ -276,7 +572,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidInstruction {
-                line: Line { num, content },
+                line: Line { num, content, .. },
             } => {
                 let margin = num.to_string().len();
                 writeln!(
@@ -291,7 +587,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidOperandCount {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 ops,
                 expected,
                 found,
@@ -317,7 +613,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidOpcode {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 opcode,
             } => {
                 let margin = num.to_string().len();
@@ -329,7 +625,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidRegister {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 register,
             } => {
                 let margin = num.to_string().len();
@@ -341,7 +637,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidTargetAddress {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 address,
             } => {
                 let margin = num.to_string().len();
@@ -357,7 +653,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::InvalidImmediate {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 immediate,
             } => {
                 let margin = num.to_string().len();
@@ -368,8 +664,45 @@ impl fmt::Display for ParserError {
                     fmt_line(*num, content, margin, false, "", true, immediate)
                 )
             }
+            Self::InvalidString {
+                line: Line { num, content, .. },
+                string,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(f, "\x1b[91merror\x1b[0m: invalid string `{}`", string)?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, string)
+                )
+            }
+            Self::UnalignedInstruction {
+                line: Line { num, content, .. },
+                opcode,
+                addr,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: instruction `{}` at unaligned address {:#010x}",
+                    opcode, addr
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        "instructions must be word aligned",
+                        true,
+                        opcode
+                    )
+                )
+            }
             Self::InvalidFloatCond {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 cond,
             } => {
                 let margin = num.to_string().len();
@@ -385,7 +718,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::BranchOutOfBounds {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 branch,
                 bounds,
             } => {
@@ -409,7 +742,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::LocalLabelOutOfScope {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 label,
             } => {
                 let margin = num.to_string().len();
@@ -425,7 +758,7 @@ impl fmt::Display for ParserError {
                 )
             }
             Self::UndefinedLabel {
-                line: Line { num, content },
+                line: Line { num, content, .. },
                 label,
             } => {
                 let margin = num.to_string().len();
@@ -436,10 +769,475 @@ impl fmt::Display for ParserError {
                     fmt_line(*num, content, margin, false, "used here", true, label)
                 )
             }
+            Self::InvalidConstant {
+                line: Line { num, content, .. },
+                constant,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: constant `{}` has an invalid value",
+                    constant
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "used here", true, constant)
+                )
+            }
+            Self::UnsupportedInstruction {
+                line: Line { num, content, .. },
+                opcode,
+                target,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: instruction `{}` is not supported on the {}",
+                    opcode, target
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, opcode)
+                )
+            }
+            Self::SectionOverflow {
+                line: Line { num, content, .. },
+                opcode,
+                section,
+                symbol,
+                over,
+            } => {
+                let margin = num.to_string().len();
+                write!(
+                    f,
+                    "\x1b[91merror\x1b[0m: `{}` overflows {} by {} byte{}",
+                    section,
+                    section.memory(),
+                    over,
+                    if *over == 1 { "" } else { "s" }
+                )?;
+                match symbol {
+                    Some(symbol) => writeln!(f, " after `{}`", symbol)?,
+                    None => writeln!(f)?,
+                }
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        &format!("crosses the end of {}", section.memory()),
+                        true,
+                        opcode
+                    )
+                )
+            }
+            Self::InvalidMacroName {
+                line: Line { num, content, .. },
+                name,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(f, "\x1b[91merror\x1b[0m: invalid macro name `{}`", name)?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        "must start with a letter",
+                        true,
+                        name
+                    )
+                )
+            }
+            Self::InvalidMacroParameter {
+                line: Line { num, content, .. },
+                param,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: invalid macro parameter `{}`",
+                    param
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, param)
+                )
+            }
+            Self::InvalidMacroArgument {
+                line: Line { num, content, .. },
+                name,
+                arg,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: unexpected argument `{}` to macro `{}`",
+                    arg, name
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, arg)
+                )
+            }
+            Self::MissingMacroArgument {
+                line: Line { num, content, .. },
+                name,
+                param,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: missing argument `{}` to macro `{}`",
+                    param, name
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, name)
+                )
+            }
+            Self::UnterminatedMacro {
+                line: Line { num, content, .. },
+                name,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: macro `{}` is missing `.endm`",
+                    name
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "defined here", true, name)
+                )
+            }
+            Self::MacroRecursion {
+                line: Line { num, content, .. },
+                name,
+                depth,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: macro `{}` expands more than {} levels deep",
+                    name, depth
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, name)
+                )
+            }
+            Self::InvalidInclude {
+                line: Line { num, content, .. },
+                path,
+                reason,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: cannot include {}: {}",
+                    path, reason
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "", true, path)
+                )
+            }
+            Self::InvalidCondition {
+                line: Line { num, content, .. },
+                condition,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(f, "\x1b[91merror\x1b[0m: invalid condition `{}`", condition)?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        "expected an expression of constants defined before",
+                        true,
+                        condition
+                    )
+                )
+            }
+            Self::UnexpectedConditional {
+                line: Line { num, content, .. },
+                directive,
+                after_else,
+            } => {
+                let margin = num.to_string().len();
+                let msg = match after_else {
+                    true => "the block already has an `.else`",
+                    false => "no `.if` block is open",
+                };
+                writeln!(f, "\x1b[91merror\x1b[0m: unexpected `{}`", directive)?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, msg, true, directive)
+                )
+            }
+            Self::UnterminatedConditional {
+                line: Line { num, content, .. },
+                directive,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "\x1b[91merror\x1b[0m: `{}` block is missing `.endif`",
+                    directive
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, "opened here", true, directive)
+                )
+            }
+            Self::InvalidAliasName {
+                line: Line { num, content, .. },
+                name,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(f, "\x1b[91merror\x1b[0m: invalid alias name `{}`", name)?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        "already names a register",
+                        true,
+                        name
+                    )
+                )
+            }
+            Self::InvalidElement {
+                line: Line { num, content, .. },
+                e,
+                element,
+            } => {
+                let margin = num.to_string().len();
+                let expected = match element {
+                    Element::Broadcast => "expected 0 to 15, `eN`, `Nq` or `Nh` with N up to 7",
+                    Element::Lane => "expected 0 to 31 or `e0` to `e7`",
+                    Element::Byte => "expected 0 to 15 or `e0` to `e7`",
+                };
+                writeln!(f, "\x1b[91merror\x1b[0m: invalid element `{}`", e)?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(*num, content, margin, false, expected, true, e)
+                )
+            }
+            Self::DeniedWarning(warning) => warning.write(f, "\x1b[91merror\x1b[0m"),
         }
     }
 }
 
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f)?;
+        self.line().write_origin(f)
+    }
+}
+
+/// The kinds of [`ParserWarning`], used to set the level each one is reported at
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum WarningKind {
+    /// A branch or jump in the delay slot of another branch or jump
+    InvalidInstructionInDelaySlot,
+    /// A branch to an address that is not a multiple of 4
+    UnalignedBranch,
+    /// A jump to an address that is not a multiple of 4
+    UnalignedJump,
+}
+
+/// How a warning is reported
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Level {
+    /// The warning is ignored
+    Allow,
+    /// The warning is returned next to the assembled program
+    #[default]
+    Warn,
+    /// The warning fails assembly as a [`ParserError::DeniedWarning`]
+    Deny,
+}
+
+#[derive(Clone, Debug)]
+pub enum ParserWarning {
+    InvalidInstructionInDelaySlot { delay_slot_inst: Line, line: Line },
+    UnalignedBranch { line: Line, offset: String },
+    UnalignedJump { line: Line, target: String },
+}
+
+impl ParserWarning {
+    /// The kind the level of this warning is set by
+    pub fn kind(&self) -> WarningKind {
+        match self {
+            Self::InvalidInstructionInDelaySlot { .. } => {
+                WarningKind::InvalidInstructionInDelaySlot
+            }
+            Self::UnalignedBranch { .. } => WarningKind::UnalignedBranch,
+            Self::UnalignedJump { .. } => WarningKind::UnalignedJump,
+        }
+    }
+
+    fn line(&self) -> &Line {
+        match self {
+            Self::InvalidInstructionInDelaySlot {
+                delay_slot_inst: line,
+                ..
+            }
+            | Self::UnalignedBranch { line, .. }
+            | Self::UnalignedJump { line, .. } => line,
+        }
+    }
+
+    fn write(&self, f: &mut fmt::Formatter, severity: &str) -> fmt::Result {
+        match self {
+            Self::InvalidInstructionInDelaySlot {
+                delay_slot_inst:
+                    Line {
+                        num: slot_num,
+                        content: slot_content,
+                        ..
+                    },
+                line: Line { num, content, .. },
+            } => {
+                let margin = cmp::max(num.to_string().len(), slot_num.to_string().len());
+                writeln!(
+                    f,
+                    "{}: `{}` in the delay slot of a branch or jump",
+                    severity,
+                    slot_content.trim()
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        true,
+                        "delay slot of this instruction",
+                        true,
+                        content.trim()
+                    )
+                )?;
+                if *slot_num > num + 1 {
+                    writeln!(f, "\x1b[94m...\x1b[0m")?;
+                }
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *slot_num,
+                        slot_content,
+                        margin,
+                        false,
+                        "",
+                        false,
+                        slot_content.trim()
+                    )
+                )
+            }
+            Self::UnalignedBranch {
+                line: Line { num, content, .. },
+                offset,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "{}: branch target `{}` is not word aligned",
+                    severity, offset
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        "the low bits are ignored",
+                        true,
+                        offset
+                    )
+                )
+            }
+            Self::UnalignedJump {
+                line: Line { num, content, .. },
+                target,
+            } => {
+                let margin = num.to_string().len();
+                writeln!(
+                    f,
+                    "{}: jump target `{}` is not word aligned",
+                    severity, target
+                )?;
+                writeln!(
+                    f,
+                    "{}",
+                    fmt_line(
+                        *num,
+                        content,
+                        margin,
+                        false,
+                        "the low bits are ignored",
+                        true,
+                        target
+                    )
+                )
+            }
+        }
+    }
+}
+
+impl fmt::Display for ParserWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.write(f, "\x1b[93mwarning\x1b[0m")?;
+        self.line().write_origin(f)
+    }
+}
+
+/// An instruction word that does not decode to any known instruction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisassemblyError {
+    /// The undecodable word
+    pub word: u32,
+    /// Byte offset of the word from the start of the input
+    pub offset: u32,
+}
+
+impl fmt::Display for DisassemblyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "\x1b[91merror\x1b[0m: invalid instruction `{:#010x}` at offset {:#x}",
+            self.word, self.offset
+        )
+    }
+}
+
 fn fmt_line(
     num: usize,
     content: &str,
@@ -450,9 +1248,12 @@ fn fmt_line(
     underline: &str,
 ) -> String {
     let mut s = String::new();
-    let underline_start = content
-        .find(underline)
-        .unwrap_or_else(|| panic!("{}", content));
+    // A line rewritten from a dialect can lack the text, so the whole
+    // statement is underlined instead
+    let (underline_start, underline) = match content.find(underline) {
+        Some(start) => (start, underline),
+        None => (content.len() - content.trim_start().len(), content.trim()),
+    };
     if first_space {
         writeln!(s, "\x1b[94m{:>margin$} |\x1b[0m", "").unwrap();
     }
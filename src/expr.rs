@@ -0,0 +1,255 @@
+use crate::parser::is_label_char;
+
+// Deepest nesting of parentheses and unary operators, to stop a long
+// expression from overflowing the stack
+const MAX_DEPTH: usize = 64;
+
+enum Error<E> {
+    Invalid,
+    Symbol(E),
+}
+
+struct Eval<'a, 's, E> {
+    input: &'a str,
+    pos: usize,
+    depth: usize,
+    symbol: &'s dyn Fn(&'a str) -> Result<i64, E>,
+}
+
+/// Evaluates an operand expression, resolving symbols and `.` with `symbol`.
+///
+/// Supports the C operators `|| && | ^ & == != < <= > >= << >> + - * / % ~ !`,
+/// parentheses and the `%hi`/`%lo` halves of an address, with `%hi` adjusted
+/// for the sign extension of `%lo`. Comparisons are 1 if true and 0 otherwise.
+/// Returns `Ok(None)` if `input` is not a valid expression, including one
+/// nested too deep.
+pub fn eval<'a, E>(
+    input: &'a str,
+    symbol: &dyn Fn(&'a str) -> Result<i64, E>,
+) -> Result<Option<i64>, E> {
+    let mut eval = Eval {
+        input,
+        pos: 0,
+        depth: 0,
+        symbol,
+    };
+    let value = eval.logical_or().and_then(|x| match eval.rest() {
+        "" => Ok(x),
+        _ => Err(Error::Invalid),
+    });
+    match value {
+        Ok(x) => Ok(Some(x)),
+        Err(Error::Invalid) => Ok(None),
+        Err(Error::Symbol(e)) => Err(e),
+    }
+}
+
+// Parses a decimal, `0x` hexadecimal or `0b` binary number of up to 64 bits,
+// with the values above `i64::MAX` wrapping around to negative ones
+fn parse_number(s: &str) -> Option<i64> {
+    let x = if let Some(hex) = s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        u64::from_str_radix(hex, 16).ok()?
+    } else if let Some(bin) = s.strip_prefix("0b").or_else(|| s.strip_prefix("0B")) {
+        u64::from_str_radix(bin, 2).ok()?
+    } else {
+        s.parse::<u64>().ok()?
+    };
+    Some(x as i64)
+}
+
+impl<'a, E> Eval<'a, '_, E> {
+    fn rest(&mut self) -> &'a str {
+        let rest = &self.input[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        &self.input[self.pos..]
+    }
+
+    // Consumes `token` if the input continues with it
+    fn eat(&mut self, token: &str) -> bool {
+        let found = self.rest().starts_with(token);
+        if found {
+            self.pos += token.len();
+        }
+        found
+    }
+
+    // Consumes the operator `op` unless it starts a longer operator
+    fn eat_op(&mut self, op: &str) -> bool {
+        let rest = self.rest();
+        let longer = ["||", "&&", "==", "!=", "<=", ">=", "<<", ">>"]
+            .iter()
+            .any(|x| x.len() > op.len() && x.starts_with(op) && rest.starts_with(x));
+        !longer && self.eat(op)
+    }
+
+    fn logical_or(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.logical_and()?;
+        while self.eat("||") {
+            let y = self.logical_and()?;
+            x = (x != 0 || y != 0) as i64;
+        }
+        Ok(x)
+    }
+
+    fn logical_and(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.or()?;
+        while self.eat("&&") {
+            let y = self.or()?;
+            x = (x != 0 && y != 0) as i64;
+        }
+        Ok(x)
+    }
+
+    fn or(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.xor()?;
+        while self.eat_op("|") {
+            x |= self.xor()?;
+        }
+        Ok(x)
+    }
+
+    fn xor(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.and()?;
+        while self.eat("^") {
+            x ^= self.and()?;
+        }
+        Ok(x)
+    }
+
+    fn and(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.equality()?;
+        while self.eat_op("&") {
+            x &= self.equality()?;
+        }
+        Ok(x)
+    }
+
+    fn equality(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.relational()?;
+        loop {
+            if self.eat("==") {
+                x = (x == self.relational()?) as i64;
+            } else if self.eat("!=") {
+                x = (x != self.relational()?) as i64;
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn relational(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.shift()?;
+        loop {
+            if self.eat("<=") {
+                x = (x <= self.shift()?) as i64;
+            } else if self.eat(">=") {
+                x = (x >= self.shift()?) as i64;
+            } else if self.eat_op("<") {
+                x = (x < self.shift()?) as i64;
+            } else if self.eat_op(">") {
+                x = (x > self.shift()?) as i64;
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn shift(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.add()?;
+        loop {
+            if self.eat("<<") {
+                x = x.wrapping_shl(self.add()? as u32);
+            } else if self.eat(">>") {
+                x = x.wrapping_shr(self.add()? as u32);
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn add(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.mul()?;
+        loop {
+            if self.eat("+") {
+                x = x.wrapping_add(self.mul()?);
+            } else if self.eat("-") {
+                x = x.wrapping_sub(self.mul()?);
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn mul(&mut self) -> Result<i64, Error<E>> {
+        let mut x = self.unary()?;
+        loop {
+            if self.eat("*") {
+                x = x.wrapping_mul(self.unary()?);
+            } else if self.eat("/") {
+                x = x.checked_div(self.unary()?).ok_or(Error::Invalid)?;
+            } else if self.eat("%") {
+                x = x.checked_rem(self.unary()?).ok_or(Error::Invalid)?;
+            } else {
+                return Ok(x);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<i64, Error<E>> {
+        if self.depth == MAX_DEPTH {
+            return Err(Error::Invalid);
+        }
+        self.depth += 1;
+        let x = self.prefix();
+        self.depth -= 1;
+        x
+    }
+
+    fn prefix(&mut self) -> Result<i64, Error<E>> {
+        if self.eat("-") {
+            Ok(self.unary()?.wrapping_neg())
+        } else if self.eat("+") {
+            self.unary()
+        } else if self.eat("~") {
+            Ok(!self.unary()?)
+        } else if self.eat_op("!") {
+            Ok((self.unary()? == 0) as i64)
+        } else {
+            self.primary()
+        }
+    }
+
+    fn primary(&mut self) -> Result<i64, Error<E>> {
+        if self.eat("%hi(") {
+            let x = self.parenthesized()?;
+            return Ok((x.wrapping_add(0x8000) >> 16) & 0xFFFF);
+        }
+        if self.eat("%lo(") {
+            return Ok(self.parenthesized()? as i16 as i64);
+        }
+        if self.eat("(") {
+            return self.parenthesized();
+        }
+
+        let rest = self.rest();
+        let len = rest.find(|c| !is_label_char(c)).unwrap_or(rest.len());
+        let token = &rest[..len];
+        self.pos += len;
+        match token.chars().next() {
+            Some(c) if c.is_ascii_digit() => parse_number(token).ok_or(Error::Invalid),
+            Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '.' => {
+                (self.symbol)(token).map_err(Error::Symbol)
+            }
+            _ => Err(Error::Invalid),
+        }
+    }
+
+    // The rest of an expression after its opening parenthesis
+    fn parenthesized(&mut self) -> Result<i64, Error<E>> {
+        let x = self.logical_or()?;
+        match self.eat(")") {
+            true => Ok(x),
+            false => Err(Error::Invalid),
+        }
+    }
+}
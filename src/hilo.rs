@@ -0,0 +1,245 @@
+use crate::ast::{self, Half, Instruction};
+use crate::error::DisassemblyError;
+use crate::target::Target;
+
+use alloc::{collections::BTreeMap, vec, vec::Vec};
+
+type I = ast::ITypeOp;
+type J = ast::JTypeOp;
+type R = ast::RTypeOp;
+type Reg = ast::Register;
+
+// What a register holds on entry to an instruction
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Def {
+    // No path reaching the instruction has been followed yet
+    Unreached,
+    // The upper half loaded by the `lui` at this index on every path
+    Lui(usize),
+    Unknown,
+}
+
+type State = [Def; 32];
+
+fn meet(a: &State, b: &State) -> State {
+    let mut state = *a;
+    for (x, y) in state.iter_mut().zip(b) {
+        *x = match (*x, *y) {
+            (Def::Unreached, y) => y,
+            (x, Def::Unreached) => x,
+            (x, y) if x == y => x,
+            _ => Def::Unknown,
+        };
+    }
+    state
+}
+
+/// Finds the `lui` instructions and the instructions adding a lower half to
+/// their register, following jumps and branches inside `insts`.
+///
+/// Returns the half and the full address built for each of them by index.
+pub fn pairs(
+    insts: &[Result<Instruction, DisassemblyError>],
+    base: u32,
+    cpu: Target,
+) -> BTreeMap<usize, (Half, u32)> {
+    let mut pairs = BTreeMap::new();
+    if insts.is_empty() {
+        return pairs;
+    }
+
+    let mut states = vec![[Def::Unreached; 32]; insts.len()];
+    states[0] = [Def::Unknown; 32];
+    let mut work = vec![0];
+    while let Some(index) = work.pop() {
+        let out = transfer(&insts[index], index, &states[index]);
+        for (next, clobbered) in successors(insts, index, base, cpu) {
+            let state = if clobbered { [Def::Unknown; 32] } else { out };
+            let merged = meet(&states[next], &state);
+            if merged != states[next] {
+                states[next] = merged;
+                work.push(next);
+            }
+        }
+    }
+
+    for (index, inst) in insts.iter().enumerate() {
+        let (op, rs, imm) = match inst {
+            Ok(Instruction::Immediate {
+                op,
+                rs,
+                imm: ast::Immediate(imm),
+                ..
+            }) => (*op, *rs, *imm),
+            _ => continue,
+        };
+        let lui = match states[index][rs.as_num() as usize] {
+            Def::Lui(lui) => lui,
+            _ => continue,
+        };
+        let hi = match &insts[lui] {
+            Ok(Instruction::Immediate {
+                imm: ast::Immediate(hi),
+                ..
+            }) => (*hi as u32) << 16,
+            _ => continue,
+        };
+        let addr = match op {
+            // `%hi` rounds up when bit 15 is set, which only matches a sign
+            // extended lower half
+            I::Ori if imm & 0x8000 == 0 => hi | imm as u32,
+            I::Addi | I::Addiu | I::Daddi | I::Daddiu => hi.wrapping_add(imm as i16 as u32),
+            op if is_memory(op) => hi.wrapping_add(imm as i16 as u32),
+            _ => continue,
+        };
+        pairs.insert(index, (Half::Lo, addr));
+        pairs.entry(lui).or_insert((Half::Hi, addr));
+    }
+    pairs
+}
+
+fn transfer(inst: &Result<Instruction, DisassemblyError>, index: usize, state: &State) -> State {
+    let mut state = *state;
+    match inst {
+        Ok(Instruction::Immediate { op: I::Lui, rt, .. }) if *rt != Reg::Zero => {
+            state[rt.as_num() as usize] = Def::Lui(index)
+        }
+        Ok(inst) => {
+            if let Some(reg) = written(inst) {
+                state[reg.as_num() as usize] = Def::Unknown;
+            }
+        }
+        Err(_) => {}
+    }
+    state
+}
+
+// The general purpose register an instruction writes, erring on the side of
+// registers that are not actually written
+fn written(inst: &Instruction) -> Option<Reg> {
+    match *inst {
+        Instruction::Immediate { op, rt, .. } => match op {
+            I::Bgezal | I::Bgezall | I::Bltzal | I::Bltzall | I::Bal => Some(Reg::Ra),
+            I::Sb | I::Sh | I::Sw | I::Swl | I::Swr | I::Sd | I::Sdl | I::Sdr => None,
+            I::Ldc1 | I::Lwc1 | I::Sdc1 | I::Swc1 | I::Cache => None,
+            I::Teqi | I::Tgei | I::Tgeiu | I::Tlti | I::Tltiu | I::Tnei => None,
+            op if op.is_branch() => None,
+            _ => Some(rt),
+        },
+        Instruction::Jump { op: J::Jal, .. } => Some(Reg::Ra),
+        Instruction::Register { op, rt, rd, .. } => match op {
+            R::Cfc0 | R::Cfc1 | R::Cfc2 | R::Dmfc0 | R::Dmfc1 | R::Mfc0 | R::Mfc1 | R::Mfc2 => {
+                Some(rt)
+            }
+            _ => Some(rd),
+        },
+        _ => None,
+    }
+}
+
+fn is_memory(op: ast::ITypeOp) -> bool {
+    matches!(
+        op,
+        I::Lb
+            | I::Lbu
+            | I::Ld
+            | I::Ldc1
+            | I::Ldl
+            | I::Ldr
+            | I::Lh
+            | I::Lhu
+            | I::Ll
+            | I::Lld
+            | I::Lw
+            | I::Lwc1
+            | I::Lwl
+            | I::Lwr
+            | I::Lwu
+            | I::Sb
+            | I::Sc
+            | I::Scd
+            | I::Sd
+            | I::Sdc1
+            | I::Sdl
+            | I::Sdr
+            | I::Sh
+            | I::Sw
+            | I::Swc1
+            | I::Swl
+            | I::Swr
+    )
+}
+
+// The instructions control can reach from `index`, and whether a call
+// clobbered the registers on the way
+fn successors(
+    insts: &[Result<Instruction, DisassemblyError>],
+    index: usize,
+    base: u32,
+    cpu: Target,
+) -> Vec<(usize, bool)> {
+    let mut next = vec![];
+    let transfer = match index.checked_sub(1).map(|x| &insts[x]) {
+        Some(Ok(inst)) if inst.has_delay_slot() => Some(inst),
+        _ => None,
+    };
+
+    match (transfer, &insts[index]) {
+        // The delay slot continues to the target of the jump or branch before it
+        (Some(inst), _) => {
+            let pc = base.wrapping_add(4 * (index as u32 - 1));
+            if let Some(target) = inst.target(pc, base, cpu) {
+                let offset = target.wrapping_sub(base);
+                if offset % 4 == 0 {
+                    next.push(((offset / 4) as usize, false));
+                }
+            }
+            match inst {
+                Instruction::Jump { op: J::Jal, .. }
+                | Instruction::Register { op: R::Jalr, .. } => next.push((index + 1, true)),
+                Instruction::Immediate { op, .. } if is_call(*op) => next.push((index + 1, true)),
+                Instruction::Immediate { op, rs, rt, .. } if !is_likely(*op) => {
+                    let always = match op {
+                        I::Beq => rs == rt,
+                        I::Bgez => *rs == Reg::Zero,
+                        _ => false,
+                    };
+                    if !always {
+                        next.push((index + 1, false));
+                    }
+                }
+                _ => {}
+            }
+        }
+        // A branch likely skips its delay slot when it is not taken
+        (None, Ok(Instruction::Immediate { op, .. })) if is_likely(*op) => {
+            next.push((index + 1, false));
+            next.push((index + 2, false));
+        }
+        _ => next.push((index + 1, false)),
+    }
+    next.retain(|(x, _)| *x < insts.len());
+    next
+}
+
+fn is_call(op: ast::ITypeOp) -> bool {
+    matches!(op, I::Bgezal | I::Bgezall | I::Bltzal | I::Bltzall | I::Bal)
+}
+
+fn is_likely(op: ast::ITypeOp) -> bool {
+    matches!(
+        op,
+        I::Bc0fl
+            | I::Bc0tl
+            | I::Bc1fl
+            | I::Bc1tl
+            | I::Beql
+            | I::Bgezall
+            | I::Bgezl
+            | I::Bgtzl
+            | I::Blezl
+            | I::Bltzall
+            | I::Bltzl
+            | I::Bnel
+    )
+}
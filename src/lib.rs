@@ -1,22 +1,49 @@
 #![no_std]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use alloc::{
+    collections::BTreeMap,
     string::{String, ToString},
     vec::Vec,
 };
-use core::marker::PhantomData;
-pub use error::ParserError;
+pub use assembler::{Sections, Usage};
+pub use dialect::Dialect;
+pub use disassembler::Endian;
+pub use error::{DisassemblyError, Level, ParserError, ParserWarning, WarningKind};
+pub use parser::Section;
+#[cfg(feature = "std")]
+pub use source::FileResolver;
+pub use source::SourceResolver;
+pub use target::Target;
 
-mod ast;
+mod assembler;
+pub mod ast;
+mod dialect;
 mod disassembler;
 mod error;
+mod expr;
+mod hilo;
+mod parser;
+mod pseudo;
+mod source;
+mod target;
 
 /// An instance of the assembler/disassembler
 pub struct Mipsasm<'a> {
     base_addr: u32,
-    _marker: PhantomData<&'a str>,
+    data_addr: u32,
+    target: Option<Target>,
+    labels: bool,
+    pseudo: bool,
+    hilo: bool,
+    levels: BTreeMap<WarningKind, Level>,
+    resolver: Option<&'a dyn SourceResolver>,
+    defines: BTreeMap<String, i64>,
+    aliases: ast::Aliases,
+    dialect: Dialect,
 }
 
 impl<'a> Default for Mipsasm<'a> {
@@ -28,42 +55,852 @@ impl<'a> Default for Mipsasm<'a> {
 impl<'a> Mipsasm<'a> {
     /// Create a new `Mipsasm` instance.
     ///
-    /// Sets the base address to 0 and the debug flag to false.
+    /// Sets the base addresses of both sections to 0, accepts the instructions of every target,
+    /// disables label generation, pseudoinstruction folding and `%hi`/`%lo` annotation and reports every warning at [`Level::Warn`].
     pub fn new() -> Mipsasm<'a> {
         Mipsasm {
             base_addr: 0,
-            _marker: PhantomData,
+            data_addr: 0,
+            target: None,
+            labels: false,
+            pseudo: false,
+            hilo: false,
+            levels: BTreeMap::new(),
+            resolver: None,
+            defines: BTreeMap::new(),
+            aliases: ast::Aliases::new(),
+            dialect: Dialect::Mipsasm,
         }
     }
 
     /// Set the base address for the assembler.
     ///
+    /// This is the address the first instruction is loaded at, jump and branch
+    /// targets wrap around inside the 4 KiB of IMEM it points into.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use mipsasm::Mipsasm;
+    /// use mipsasm_rsp::Mipsasm;
     ///
     /// let mut mipsasm = Mipsasm::new();
-    /// mipsasm.base(0x8000_0000);
+    /// mipsasm.base(0x0400_1000);
+    /// assert_eq!(mipsasm.disassemble(&[0x08000420]), vec!["j      0x4001080"]);
     /// ```
     pub fn base(&mut self, addr: u32) -> &mut Mipsasm<'a> {
         self.base_addr = addr;
         self
     }
 
+    /// Set the base address of the `.data` section for the assembler.
+    ///
+    /// This is the address the data is loaded at, usually DMEM while the code
+    /// set by [`Mipsasm::base`] runs from IMEM.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_1000).data_base(0x0400_0000);
+    /// let sections = mipsasm.assemble_sections(
+    ///     ".data
+    ///  value:
+    ///      .word   0x1234
+    ///      .text
+    ///      lw      t0, %lo(value)(zero)",
+    /// );
+    /// assert_eq!(sections.unwrap().text, vec![0x8c080000]);
+    /// ```
+    pub fn data_base(&mut self, addr: u32) -> &mut Mipsasm<'a> {
+        self.data_addr = addr;
+        self
+    }
+
+    /// Restrict the assembler and disassembler to the instructions `target` can execute.
+    ///
+    /// Jump and branch targets are also resolved the way `target` computes them,
+    /// without a target they wrap around inside IMEM like on the RSP.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Mipsasm, Target};
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.target(Target::Rsp);
+    /// assert!(mipsasm.assemble("mult a0, a1").is_err());
+    /// assert_eq!(mipsasm.disassemble(&[0x00850018]), vec![".word   0x00850018"]);
+    ///
+    /// mipsasm.target(Target::Vr4300).base(0x8000_0400);
+    /// assert!(mipsasm.assemble("vaddc $v1, $v2, $v3[5]").is_err());
+    /// assert_eq!(mipsasm.disassemble(&[0x08000420]), vec!["j      0x80001080"]);
+    /// assert_eq!(
+    ///     mipsasm.assemble("dli a0, 0x123456789abcdef0").unwrap(),
+    ///     vec![0x3c041234, 0x34845678, 0x00042438, 0x34849abc, 0x00042438, 0x3484def0]
+    /// );
+    /// ```
+    pub fn target(&mut self, target: Target) -> &mut Mipsasm<'a> {
+        self.target = Some(target);
+        self
+    }
+
+    /// Disassemble common instruction idioms as the pseudoinstruction they are
+    /// written as: `nop`, `b`, `beqz`, `bnez`, `li`, `move`, `neg`, `negu`, `not` and `vnop`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.pseudo(true);
+    /// assert_eq!(
+    ///     mipsasm.disassemble(&[0x00000000, 0x2408fffb, 0x00a02021, 0x1000fffc, 0x4a000037]),
+    ///     vec!["nop", "li      t0, -0x5", "move    a0, a1", "b       0x0", "vnop"]
+    /// );
+    /// ```
+    pub fn pseudo(&mut self, enable: bool) -> &mut Mipsasm<'a> {
+        self.pseudo = enable;
+        self
+    }
+
+    /// Disassemble the immediates of a `lui` and the instructions adding a lower
+    /// half to its register as `%hi`/`%lo` of the address they build.
+    ///
+    /// Pairs are followed across jumps and branches. The address is shown as a
+    /// generated label when labels are enabled and it is inside the listing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.hilo(true);
+    /// assert_eq!(
+    ///     mipsasm.disassemble(&[0x3c040400, 0x8c851080]),
+    ///     vec!["lui     a0, %hi(0x4001080)", "lw      a1, %lo(0x4001080)(a0)"]
+    /// );
+    ///
+    /// mipsasm.base(0x0400_1000).labels(true);
+    /// assert_eq!(
+    ///     mipsasm.disassemble(&[0x3c040400, 0x24841008, 0x00000000]),
+    ///     vec!["lui     a0, %hi(.L0008)", "addiu   a0, a0, %lo(.L0008)", ".L0008:", "sll     zero, zero, 0x0"]
+    /// );
+    /// ```
+    pub fn hilo(&mut self, enable: bool) -> &mut Mipsasm<'a> {
+        self.hilo = enable;
+        self
+    }
+
+    /// Set the level `kind` warnings are reported at.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Level, Mipsasm, WarningKind};
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// let (_, warnings) = mipsasm.assemble_with_warnings("j 0x102").unwrap();
+    /// assert_eq!(warnings[0].kind(), WarningKind::UnalignedJump);
+    ///
+    /// mipsasm.warning(WarningKind::UnalignedJump, Level::Deny);
+    /// assert!(mipsasm.assemble("j 0x102").is_err());
+    /// ```
+    pub fn warning(&mut self, kind: WarningKind, level: Level) -> &mut Mipsasm<'a> {
+        self.levels.insert(kind, level);
+        self
+    }
+
+    /// Generate labels for jump and branch targets when disassembling.
+    ///
+    /// Targets outside of the disassembled words are printed as addresses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_1000);
+    /// assert_eq!(
+    ///     mipsasm.disassemble(&[0x1480ffff, 0x00000000]),
+    ///     vec!["bne     a0, zero, 0x4001000", "sll     zero, zero, 0x0"]
+    /// );
+    ///
+    /// mipsasm.labels(true);
+    /// assert_eq!(
+    ///     mipsasm.disassemble(&[0x1480ffff, 0x00000000]),
+    ///     vec![".L0000:", "bne     a0, zero, .L0000", "sll     zero, zero, 0x0"]
+    /// );
+    /// ```
+    pub fn labels(&mut self, enable: bool) -> &mut Mipsasm<'a> {
+        self.labels = enable;
+        self
+    }
+
+    /// Read the files named by `.include "path"` and `.incbin "path", offset, size` with `resolver`.
+    ///
+    /// `.include` assembles the lines of the file in place of the directive,
+    /// `.incbin` writes the bytes of the file from `offset`, all of them by default.
+    /// Paths in included files are relative to the directory of the file.
+    /// With the `std` feature, [`FileResolver`] reads them from the filesystem.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Mipsasm, SourceResolver};
+    ///
+    /// struct Files;
+    ///
+    /// impl SourceResolver for Files {
+    ///     fn resolve(&self, path: &str) -> Result<Vec<u8>, String> {
+    ///         match path {
+    ///             "inc/regs.inc" => Ok(b".include \"consts.inc\"\n.equ ONE, 1".to_vec()),
+    ///             "inc/consts.inc" => Ok(b".equ TWO, 2".to_vec()),
+    ///             "table.bin" => Ok(vec![1, 2, 3, 4, 5, 6]),
+    ///             _ => Err("file not found".to_string()),
+    ///         }
+    ///     }
+    /// }
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.resolver(&Files);
+    /// let words = mipsasm.assemble(
+    ///     ".include \"inc/regs.inc\"
+    ///      addiu   t0, zero, ONE + TWO
+    ///      .incbin \"table.bin\", 2, 4",
+    /// );
+    /// assert_eq!(words.unwrap(), vec![0x24080003, 0x03040506]);
+    /// assert!(mipsasm.assemble(".include \"missing.inc\"").is_err());
+    ///
+    /// fn shared<T: Send + Sync>(_: &T) {}
+    /// shared(&mipsasm);
+    /// ```
+    pub fn resolver(&mut self, resolver: &'a dyn SourceResolver) -> &mut Mipsasm<'a> {
+        self.resolver = Some(resolver);
+        self
+    }
+
+    /// Define the constant `name` for every program assembled, unless the program defines it itself.
+    ///
+    /// Lines between `.if expr`, `.ifdef name` or `.ifndef name` and the next
+    /// `.elseif expr`, `.else` or `.endif` are only assembled if the condition
+    /// holds. Conditions can use the constants defined here and the constants
+    /// and labels defined before them, but not the addresses of labels.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let source = ".ifndef VTX_CACHE
+    ///      .equ    VTX_CACHE, 16
+    ///      .endif
+    ///      .if     VTX_CACHE >= 32
+    ///      addiu   t0, zero, VTX_CACHE * 40
+    ///      .elseif VTX_CACHE >= 16
+    ///      addiu   t0, zero, VTX_CACHE * 32
+    ///      .else
+    ///      break
+    ///      .endif
+    ///      .ifdef  PROFILE
+    ///      addiu   t1, t1, 1
+    ///      .endif";
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// assert_eq!(mipsasm.assemble(source).unwrap(), vec![0x24080200]);
+    ///
+    /// mipsasm.define("VTX_CACHE", 32).define("PROFILE", 1);
+    /// assert_eq!(mipsasm.assemble(source).unwrap(), vec![0x24080500, 0x25290001]);
+    /// ```
+    pub fn define(&mut self, name: &str, value: i64) -> &mut Mipsasm<'a> {
+        self.defines.insert(name.to_string(), value);
+        self
+    }
+
+    /// Name registers for every program assembled and in disassembled listings.
+    ///
+    /// Programs name registers with `.alias name, reg`, or by defining a
+    /// constant to a `$` register with `.equ`, `.set` or `=`, and replace the
+    /// aliases given here. An alias defined between `.func name`, which also
+    /// defines the label `name`, and `.endfunc` ends with the function, others
+    /// last to the end of the program. Aliases can not be register names and
+    /// can not be redefined while they are in scope.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::ast::{Aliases, Register, VuRegister};
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut aliases = Aliases::new();
+    /// aliases.register("ptr", Register::A0).vu_register("vzero", VuRegister::V0);
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.aliases(aliases);
+    /// let words = mipsasm.assemble(
+    ///     ".alias  vtmp, $v1
+    ///      lqv     vtmp[0], 0x10(ptr)
+    ///      .func   clear
+    ///      .set    count, $t0
+    ///      vxor    vzero, vzero, vzero[0]
+    ///      addiu   count, count, -1
+    ///      .endfunc",
+    /// );
+    /// let words = words.unwrap();
+    /// assert_eq!(
+    ///     mipsasm.disassemble(&words),
+    ///     vec![
+    ///         "lqv     v1[0], 0x10(ptr)",
+    ///         "vxor    vzero vzero vzero[0]",
+    ///         "addiu   t0, t0, -0x1",
+    ///     ]
+    /// );
+    ///
+    /// // `count` ends with its function
+    /// assert!(mipsasm.assemble(".func a\n.set count, $t0\n.endfunc\naddiu count, count, 1").is_err());
+    /// assert!(mipsasm.assemble(".alias v2, $v1").is_err());
+    ///
+    /// // Labels, including the ones of macros, do not end a function
+    /// let words = mipsasm.assemble(
+    ///     r#".macro  wait reg
+    ///      w\@:   bnez    \reg, w\@
+    ///              nop
+    ///      .endm
+    ///      .func   copy
+    ///      .alias  src, $t1
+    ///  loop:
+    ///      wait    src
+    ///      addiu   src, src, -1
+    ///      b       loop
+    ///      .endfunc"#,
+    /// );
+    /// assert_eq!(
+    ///     words.unwrap(),
+    ///     vec![0x1520ffff, 0x00000000, 0x2529ffff, 0x1000fffc]
+    /// );
+    /// ```
+    pub fn aliases(&mut self, aliases: ast::Aliases) -> &mut Mipsasm<'a> {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Read programs written for another assembler.
+    ///
+    /// Directives are renamed to the ones of this crate, which also stay
+    /// available:
+    ///
+    /// - armips: `.db`, `.dh`/`.halfword`, `.dw`, `.dd`/`.doubleword`,
+    ///   `.fill size, value`, `.align bytes`, `.import`, `.endmacro`,
+    ///   `.definelabel` and `name equ value`, with `.rsp` ignored.
+    /// - bass: `db`, `dw`, `dd` and `dq` for 8, 16, 32 and 64 bits,
+    ///   `fill size, value`, `align bytes`, `insert name, "file"`, `include`
+    ///   and `constant name(value)`, with `arch` ignored.
+    /// - GNU as: `.asciz`/`.string`, `.hword`/`.short`, `.long`/`.int`,
+    ///   `.quad`, `.skip`, `.p2align`, `.balign` and `.section`, with symbol
+    ///   directives such as `.globl` and options such as `.set noreorder`
+    ///   ignored. `#define`, `#include` and `#if` lines of the C preprocessor
+    ///   are read as `.equ`, `.alias`, `.include` and `.if`, and
+    ///   `#include <rsp.inc>` is built in along with its `COP0_*` registers.
+    ///   Vector loads and stores also take `vt, e, offset, base`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Dialect, Mipsasm, Sections};
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.dialect(Dialect::Armips);
+    /// let words = mipsasm.assemble(
+    ///     ".rsp
+    ///      .definelabel STEP, 4   ; loop increment
+    ///  SIZE equ 8
+    ///  /* the loop
+    ///     waits on a1 */
+    ///  loop:
+    ///      addiu   a0, a0, STEP
+    ///  @@wait:
+    ///      bne     a0, a1, @@wait
+    ///      vaddc   $v1, $v2, $v3[5]
+    ///      .dh     1, 2
+    ///      .db     \"ok\", 0, 1
+    ///      addiu   a1, a1, SIZE",
+    /// );
+    /// assert_eq!(
+    ///     words.unwrap(),
+    ///     vec![0x24840004, 0x1485ffff, 0x4ba31054, 0x00010002, 0x6f6b0001, 0x24a50008]
+    /// );
+    ///
+    /// mipsasm.dialect(Dialect::Bass);
+    /// let words = mipsasm.assemble(
+    ///     "arch n64.rsp
+    ///      constant STEP($10)
+    ///      addiu a0,a0,STEP; vaddc v1,v2,v3[e13] // element 5
+    ///      dw $1234; db \"a;b\", 0",
+    /// );
+    /// assert_eq!(words.unwrap(), vec![0x24840010, 0x4ba31054, 0x1234613b, 0x62000000]);
+    ///
+    /// mipsasm.dialect(Dialect::Gnu);
+    /// let sections = mipsasm.assemble_sections(
+    ///     "#include <rsp.inc>
+    ///  #define vone $v01
+    ///  #define PASS 2
+    ///      .set    noreorder
+    ///      .globl  main
+    ///  main:
+    ///      mfc0    t0, COP0_SP_STATUS   # read the status
+    ///      vaddc   vone, $v02, $v03.e5; lqv $v04, 0, 0x10, s0
+    ///      /* padding */ .balign 16
+    ///  #if PASS == 1
+    ///      nop
+    ///  #elif PASS == 2
+    ///      break
+    ///  #else
+    ///      syscall
+    ///  #endif
+    ///      .section .rodata
+    ///      .long   0x12345678",
+    /// );
+    /// assert_eq!(
+    ///     sections.unwrap(),
+    ///     Sections {
+    ///         text: vec![0x40082000, 0x4ba31054, 0xca042001, 0x00000000, 0x0000000d],
+    ///         data: vec![0x12, 0x34, 0x56, 0x78],
+    ///     }
+    /// );
+    /// ```
+    pub fn dialect(&mut self, dialect: Dialect) -> &mut Mipsasm<'a> {
+        self.dialect = dialect;
+        self
+    }
+
+    /// Assembles a set of MIPS instructions, returning the `.text` section.
+    ///
+    /// Labels are resolved relative to the base address.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_1000);
+    /// let words = mipsasm.assemble(
+    ///     "start:
+    ///         mult    a0, a1
+    ///         vaddc   $v1, $v2, $v3[5]
+    ///         bne     a0, zero, start
+    ///         j       start",
+    /// );
+    /// assert_eq!(words.unwrap(), vec![0x00850018, 0x4aa31054, 0x1480fffd, 0x09000400]);
+    /// ```
+    ///
+    /// Immediates, offsets and targets are expressions of numbers, labels and `.`,
+    /// the address of the current instruction. `%hi` and `%lo` split an address
+    /// for a `lui` and the sign extended immediate adding to it, `andi`, `ori`
+    /// and `xori` take the 16 bits of `%lo`.
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_8000);
+    /// let words = mipsasm.assemble(
+    ///     "lui     a0, %hi(table)
+    ///      lw      a1, %lo(table + 4)(a0)
+    ///      ori     a3, a0, %lo(table)
+    ///      b       . + 8
+    ///      ori     a2, zero, (1 << 4) | 0x3
+    ///  table:",
+    /// );
+    /// assert_eq!(
+    ///     words.unwrap(),
+    ///     vec![0x3c040401, 0x8c858018, 0x34878014, 0x10000001, 0x34060013]
+    /// );
+    /// ```
+    ///
+    /// Constants are defined with `.equ name, value`, `.set name, value` or
+    /// `name = value` and can be used before their definition.
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let words = mipsasm.assemble(
+    ///     ".equ    BUFFER, 0x100
+    ///      SIZE = 0x10
+    ///      lw      t0, BUFFER + SIZE(zero)
+    ///      addiu   t1, zero, COUNT
+    ///      .set    COUNT, SIZE / 4",
+    /// );
+    /// assert_eq!(words.unwrap(), vec![0x8c080110, 0x24090004]);
+    /// ```
+    ///
+    /// Vector elements are written as `vt[e]`, `vt.e` or `vt,e(N)`. A number
+    /// is the encoded element, `eN` selects halfword N, and `Nq` and `Nh`
+    /// the quarter or half halfword N is in. Loads, stores and moves take a
+    /// byte number or `eN` for the first byte of halfword N.
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let words = mipsasm.assemble(
+    ///     "vaddc   $v1, $v2, $v3[e3]
+    ///      vaddc   $v1, $v2, $v3.e3
+    ///      vaddc   $v1, $v2, $v3,e(3)
+    ///      vaddc   $v1, $v2, $v3[1h]
+    ///      vaddc   $v1, $v2, $v3[5]
+    ///      lsv     $v1.e3, 0(s0)",
+    /// );
+    /// assert_eq!(
+    ///     words.unwrap(),
+    ///     vec![0x4b631054, 0x4b631054, 0x4b631054, 0x4aa31054, 0x4aa31054, 0xca010b00]
+    /// );
+    /// assert!(mipsasm.assemble("vaddc $v1, $v2, $v3[e8]").is_err());
+    /// assert!(mipsasm.assemble("lqv $v1[1q], 0(s0)").is_err());
+    /// ```
+    ///
+    /// Data is written with `.byte`, `.half`, `.word`, `.dword`, `.ascii`,
+    /// `.asciiz`, `.space size, fill` and `.fill repeat, size, value`, with
+    /// strings also taken by `.byte`.
+    /// `.align n` pads to a multiple of 2^n bytes and `.balign n` to a multiple
+    /// of n bytes, with `nop`s in `.text`.
+    /// The last word is padded with zero bytes.
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let words = mipsasm.assemble(
+    ///     "lqv     $v1[0], consts(zero)
+    ///      .align  4
+    ///  consts:
+    ///      .dword  0x123456789
+    ///      .half   1, -2, 0x8000
+    ///      .asciiz \"ok\"",
+    /// );
+    /// assert_eq!(
+    ///     words.unwrap(),
+    ///     vec![
+    ///         0xc8012001, 0x00000000, 0x00000000, 0x00000000, 0x00000001, 0x23456789, 0x0001fffe,
+    ///         0x80006f6b, 0x00000000
+    ///     ]
+    /// );
+    /// ```
+    ///
+    /// `.macro name params` up to `.endm` defines a macro, invoked like an
+    /// instruction with arguments in order or as `param=value`. In its body
+    /// `\param` is replaced with the argument, the default given as `param=default`
+    /// or nothing, and `\@` with a number unique to the expansion. Arguments
+    /// for `param:req` parameters are required. Macros can invoke other macros
+    /// and themselves up to 64 levels deep.
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let words = mipsasm.assemble(
+    ///     r#".macro  mul32 dst, a, b, tmp=$v29
+    ///          vmudl   \tmp, \a, \b
+    ///          vmadm   \tmp, \a, \b
+    ///          vmadn   \dst, \a, \b
+    ///      .endm
+    ///      .macro  wait reg:req
+    ///      loop\@:
+    ///          bnez    \reg, loop\@
+    ///          addiu   \reg, \reg, -1
+    ///      .endm
+    ///          mul32   $v1, $v2, $v3
+    ///          wait    t0
+    ///          mul32   b=$v3, a=$v2, dst=$v4, tmp=$v5"#,
+    /// );
+    /// assert_eq!(
+    ///     words.unwrap(),
+    ///     vec![0x4a031744, 0x4a03174d, 0x4a03104e, 0x1500ffff, 0x2508ffff, 0x4a031144, 0x4a03114d, 0x4a03110e]
+    /// );
+    /// ```
+    pub fn assemble(&self, input: &'a str) -> Result<Vec<u32>, Vec<ParserError>> {
+        self.assemble_with_warnings(input).map(|(words, _)| words)
+    }
+
+    /// Assembles a program into its `.text` and `.data` sections.
+    ///
+    /// Statements go into `.text` until a `.data` directive, `.text` switches
+    /// back. Each section continues at its own address and labels can be used
+    /// from either section.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Mipsasm, Sections};
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_1000).data_base(0x0400_0000);
+    /// let sections = mipsasm.assemble_sections(
+    ///     "start:
+    ///         lw      t0, %lo(table)(zero)
+    ///         jr      t0
+    ///         .data
+    ///     table:
+    ///         .word   start, done
+    ///         .text
+    ///     done:
+    ///         break",
+    /// );
+    /// assert_eq!(
+    ///     sections.unwrap(),
+    ///     Sections {
+    ///         text: vec![0x8c080000, 0x01000008, 0x0000000d],
+    ///         data: vec![0x04, 0x00, 0x10, 0x00, 0x04, 0x00, 0x10, 0x08],
+    ///     }
+    /// );
+    /// ```
+    pub fn assemble_sections(&self, input: &'a str) -> Result<Sections, Vec<ParserError>> {
+        let (sections, _) = self.parse(input, assembler::assemble)?;
+        Ok(sections)
+    }
+
+    /// Reports how many bytes `section` of an assembled program uses and how many
+    /// are left before the end of IMEM or DMEM, counted from the section's base address.
+    ///
+    /// Unless the target is [`Target::Vr4300`], assembling a program whose
+    /// sections do not fit into the 4 KiB of IMEM and DMEM fails with an error
+    /// naming the last symbol before the boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Mipsasm, Section, Usage};
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_1f00);
+    /// let sections = mipsasm
+    ///     .assemble_sections(
+    ///         "break
+    ///          .data
+    ///          .space 0x100",
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(mipsasm.usage(&sections, Section::Text), Usage { used: 4, free: 0xfc });
+    /// assert_eq!(mipsasm.usage(&sections, Section::Data), Usage { used: 0x100, free: 0xf00 });
+    ///
+    /// assert!(mipsasm.assemble("loop: .space 0x100\n break").is_err());
+    /// ```
+    pub fn usage(&self, sections: &Sections, section: Section) -> Usage {
+        let (used, base) = match section {
+            Section::Text => (4 * sections.text.len() as u32, self.base_addr),
+            Section::Data => (sections.data.len() as u32, self.data_addr),
+        };
+        Usage {
+            used,
+            free: parser::capacity(base).saturating_sub(used),
+        }
+    }
+
+    /// Assembles a set of MIPS instructions, also returning the warnings that
+    /// are not allowed or denied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Mipsasm, WarningKind};
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let (words, warnings) = mipsasm
+    ///     .assemble_with_warnings(
+    ///         "loop:
+    ///             beq     a0, zero, loop
+    ///             j       loop",
+    ///     )
+    ///     .unwrap();
+    /// assert_eq!(words, vec![0x1080ffff, 0x08000000]);
+    /// assert_eq!(warnings[0].kind(), WarningKind::InvalidInstructionInDelaySlot);
+    /// ```
+    pub fn assemble_with_warnings(
+        &self,
+        input: &'a str,
+    ) -> Result<(Vec<u32>, Vec<ParserWarning>), Vec<ParserError>> {
+        let (sections, warnings) = self.parse(input, assembler::assemble)?;
+        Ok((sections.text, warnings))
+    }
+
+    /// Assembles a set of MIPS instructions into a listing of the address,
+    /// machine word and instruction of every word.
+    ///
+    /// Pseudoinstructions are listed as written, followed by the instructions
+    /// they expand to. Data is listed four bytes a line next to its directive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::Mipsasm;
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// mipsasm.base(0x0400_1000);
+    /// let listing = mipsasm.listing(
+    ///     "start:
+    ///         li      t0, 0x12345678
+    ///         bge     t0, a0, start",
+    /// );
+    /// assert_eq!(
+    ///     listing.unwrap(),
+    ///     vec![
+    ///         "start:",
+    ///         "                    li      t0, 0x12345678",
+    ///         "04001000  3c081234      lui     t0, 0x1234",
+    ///         "04001004  35085678      ori     t0, t0, 0x5678",
+    ///         "                    bge     t0, a0, start",
+    ///         "04001008  0104082a      slt     at, t0, a0",
+    ///         "0400100c  1020fffc      beq     at, zero, start",
+    ///     ]
+    /// );
+    /// ```
+    pub fn listing(&self, input: &'a str) -> Result<Vec<String>, Vec<ParserError>> {
+        let (listing, _) = self.parse(input, |statements| {
+            assembler::listing(statements, self.base_addr, self.cpu())
+        })?;
+        Ok(listing)
+    }
+
+    /// Decodes a set of MIPS instructions, one result per word.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::ast::{Instruction, RTypeOp, Register};
+    /// use mipsasm_rsp::{DisassemblyError, Mipsasm};
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let instructions = mipsasm.decode(&[0x00850018, 0x7fffffff]);
+    /// assert_eq!(
+    ///     instructions,
+    ///     vec![
+    ///         Ok(Instruction::Register {
+    ///             op: RTypeOp::Mult,
+    ///             rs: Register::A0,
+    ///             rt: Register::A1,
+    ///             rd: Register::Zero,
+    ///             sa: 0,
+    ///         }),
+    ///         Err(DisassemblyError { word: 0x7fffffff, offset: 4 }),
+    ///     ]
+    /// );
+    /// ```
+    pub fn decode(&self, input: &[u32]) -> Vec<Result<ast::Instruction, DisassemblyError>> {
+        disassembler::disassemble(input.to_vec(), self.target)
+    }
+
     /// Disassembles a set of MIPS instructions.
     ///
+    /// Words that do not decode to an instruction are rendered as `.word` directives.
+    ///
     /// # Examples
     ///
     /// ```
-    /// use mipsasm::Mipsasm;
+    /// use mipsasm_rsp::Mipsasm;
     ///
     /// let mut mipsasm = Mipsasm::new();
-    /// let instructions = mipsasm.disassemble(&[0x00850018]);
-    /// assert_eq!(instructions, vec!["mult    a0, a1"]);
+    /// let instructions = mipsasm.disassemble(&[0x00850018, 0xc8812001, 0x7fffffff]);
+    /// assert_eq!(
+    ///     instructions,
+    ///     vec!["mult    a0, a1", "lqv     v1[0], 0x10(a0)", ".word   0x7fffffff"]
+    /// );
     /// ```
     pub fn disassemble(&self, input: &[u32]) -> Vec<String> {
-        let x = disassembler::disassemble(input.to_vec());
-        x.iter().map(|x| x.to_string()).collect::<Vec<String>>()
+        let x = disassembler::disassemble(input.to_vec(), self.target);
+        disassembler::listing(&x, &self.listing_options())
+    }
+
+    /// Disassembles MIPS instructions stored as bytes in the given byte order.
+    ///
+    /// Bytes after the last whole word are rendered as a `.byte` directive, in
+    /// the order they appear in the input.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{Endian, Mipsasm};
+    ///
+    /// let mipsasm = Mipsasm::new();
+    /// let instructions = mipsasm.disassemble_bytes(&[0x00, 0x85, 0x00, 0x18], Endian::Big);
+    /// assert_eq!(instructions, vec!["mult    a0, a1"]);
+    ///
+    /// let instructions = mipsasm.disassemble_bytes(&[0x18, 0x00, 0x85, 0x00], Endian::Little);
+    /// assert_eq!(instructions, vec!["mult    a0, a1"]);
+    ///
+    /// let instructions =
+    ///     mipsasm.disassemble_bytes(&[0x85, 0x00, 0x18, 0x00, 0x12, 0x34], Endian::ByteSwapped);
+    /// assert_eq!(instructions, vec!["mult    a0, a1", ".byte   0x12, 0x34"]);
+    /// ```
+    pub fn disassemble_bytes(&self, input: &[u8], endian: Endian) -> Vec<String> {
+        let (words, rest) = disassembler::words(input, endian);
+        let mut lines = self.disassemble(&words);
+        if !rest.is_empty() {
+            lines.push(disassembler::partial_word(rest));
+        }
+        lines
+    }
+
+    /// Disassembles a set of MIPS instructions, failing on the first word that
+    /// does not decode to an instruction.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use mipsasm_rsp::{DisassemblyError, Mipsasm};
+    ///
+    /// let mut mipsasm = Mipsasm::new();
+    /// let instructions = mipsasm.try_disassemble(&[0x00850018]);
+    /// assert_eq!(instructions, Ok(vec!["mult    a0, a1".to_string()]));
+    ///
+    /// let instructions = mipsasm.try_disassemble(&[0x00850018, 0x7fffffff]);
+    /// assert_eq!(instructions, Err(DisassemblyError { word: 0x7fffffff, offset: 4 }));
+    /// ```
+    pub fn try_disassemble(&self, input: &[u32]) -> Result<Vec<String>, DisassemblyError> {
+        let x = disassembler::disassemble(input.to_vec(), self.target);
+        if let Some(Err(e)) = x.iter().find(|x| x.is_err()) {
+            return Err(*e);
+        }
+        Ok(disassembler::listing(&x, &self.listing_options()))
+    }
+
+    fn listing_options(&self) -> disassembler::ListingOptions<'_> {
+        disassembler::ListingOptions {
+            base: self.base_addr,
+            cpu: self.cpu(),
+            labels: self.labels,
+            pseudo: self.pseudo,
+            hilo: self.hilo,
+            aliases: &self.aliases,
+        }
+    }
+
+    // Parses `input` after expanding its macros and hands the statements to `f`
+    fn parse<T>(
+        &self,
+        input: &str,
+        f: impl FnOnce(&[parser::Statement]) -> T,
+    ) -> Result<(T, Vec<ParserWarning>), Vec<ParserError>> {
+        let source = source::Source::new(input, self.resolver, &self.defines, self.dialect)?;
+        let (statements, warnings) = parser::Parser::new(
+            &source,
+            &self.defines,
+            &self.aliases,
+            self.base_addr,
+            self.data_addr,
+            self.target,
+            self.levels.clone(),
+        )
+        .parse()?;
+        Ok((f(&statements), warnings))
+    }
+
+    // Addresses are resolved like on the RSP unless another target is selected
+    fn cpu(&self) -> Target {
+        self.target.unwrap_or(Target::Rsp)
     }
 }
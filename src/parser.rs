@@ -0,0 +1,1707 @@
+use crate::ast;
+use crate::dialect::Dialect;
+use crate::error;
+use crate::error::{Level, Line, ParserError, ParserWarning, WarningKind};
+use crate::expr;
+use crate::pseudo;
+use crate::source::Source;
+use crate::target::Target;
+use crate::warning;
+
+use alloc::{
+    collections::{BTreeMap, BTreeSet},
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::cell::RefCell;
+use core::fmt;
+use core::str::FromStr;
+use strum_macros::EnumString;
+
+type I = ast::ITypeOp;
+type J = ast::JTypeOp;
+type R = ast::RTypeOp;
+type V = ast::VTypeOp;
+type VM = ast::VMemTypeOp;
+type Reg = ast::Register;
+type Vu = ast::VuRegister;
+
+struct Label {
+    addr: u32,
+    line: usize,
+}
+
+// A `.equ`, `.set` or `=` definition, evaluated where it is defined
+struct Constant<'a> {
+    value: &'a str,
+    addr: u32,
+    scope: &'a str,
+    line: usize,
+}
+
+// A `.alias` definition, or a `.equ`, `.set` or `=` definition of a register
+struct Alias<'a> {
+    reg: &'a str,
+    // Whether the alias is defined between `.func` and `.endfunc` and ends
+    // with the function, instead of lasting to the end of the input
+    local: bool,
+    line: usize,
+}
+
+// Assembler directives, written with a leading `.`
+#[derive(Clone, Copy, Debug, EnumString, PartialEq, Eq)]
+#[strum(ascii_case_insensitive)]
+#[strum(serialize_all = "snake_case")]
+enum Directive {
+    Byte,
+    Half,
+    Word,
+    Dword,
+    Ascii,
+    Asciiz,
+    Space,
+    Fill,
+    Align,
+    Balign,
+    Incbin,
+    Text,
+    Data,
+    Func,
+    Endfunc,
+}
+
+/// The sections a program is assembled into, each with its own addresses
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Section {
+    /// Code, loaded into IMEM on the RSP
+    Text,
+    /// Data, loaded into DMEM on the RSP
+    Data,
+}
+
+impl fmt::Display for Section {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Section::Text => write!(f, ".text"),
+            Section::Data => write!(f, ".data"),
+        }
+    }
+}
+
+impl Section {
+    /// The RSP memory the section is loaded into
+    pub fn memory(&self) -> &'static str {
+        match self {
+            Section::Text => "IMEM",
+            Section::Data => "DMEM",
+        }
+    }
+}
+
+/// The operand a vector element selects, which decides the element forms it
+/// can be written in and how they are encoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Element {
+    /// The elements of `vt` broadcast by a computational instruction, a number
+    /// up to 15 or `eN`, `Nq` or `Nh` encoded as 8 + N, 2 + N % 2 and 4 + N % 4.
+    /// armips writes `eN` as N and bass writes the number as `eN`.
+    Broadcast,
+    /// The destination lane of `vrcp` and `vrsq`, a number up to 31 or `eN`
+    Lane,
+    /// The first byte of a load, store or move, a number up to 15 or `eN`
+    /// for the byte of halfword N
+    Byte,
+}
+
+// The forms of an element selector besides a number
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Selector {
+    // `eN` or `e(N)`, halfword N
+    Lane,
+    // `Nq` or `qN`, the halfwords of the quarter N is in
+    Quarter,
+    // `Nh` or `hN`, the halfwords of the half N is in
+    Half,
+}
+
+/// Bytes from `base` to the end of the 4 KiB of IMEM or DMEM it points into
+pub fn capacity(base: u32) -> u32 {
+    0x1000 - (base & 0xFFF)
+}
+
+/// A source statement and the instructions or data it assembles to
+pub struct Statement<'a> {
+    /// Labels defined since the previous statement
+    pub labels: Vec<&'a str>,
+    pub mnemonic: &'a str,
+    pub ops: &'a str,
+    pub addr: u32,
+    pub section: Section,
+    pub insts: Vec<ast::Instruction>,
+    /// Bytes of a data directive
+    pub data: Vec<u8>,
+}
+
+impl Statement<'_> {
+    /// Returns whether the statement is a pseudoinstruction expanded by the assembler
+    pub fn is_pseudo(&self) -> bool {
+        matches!(I::from_str(self.mnemonic), Ok(op) if op.is_pseudo())
+            || matches!(R::from_str(self.mnemonic), Ok(op) if op.is_pseudo())
+    }
+}
+
+pub struct Parser<'a> {
+    source: &'a Source<'a>,
+    // Constants defined outside of the input
+    defines: &'a BTreeMap<String, i64>,
+    // Register aliases defined outside of the input
+    names: &'a ast::Aliases,
+    dialect: Dialect,
+    input: Vec<&'a str>,
+    line_num: usize,
+    // Address of the statement being parsed, the value of `.`
+    addr: u32,
+    base_addr: u32,
+    data_addr: u32,
+    target: Option<Target>,
+    levels: BTreeMap<WarningKind, Level>,
+    labels: BTreeMap<String, Label>,
+    local_labels: BTreeSet<&'a str>,
+    constants: BTreeMap<&'a str, Constant<'a>>,
+    aliases: BTreeMap<&'a str, Alias<'a>>,
+    // Constants being evaluated, to stop at a definition using itself
+    evaluating: RefCell<BTreeSet<&'a str>>,
+    scope: &'a str,
+    // Whether the statement is between `.func` and `.endfunc`
+    function: bool,
+    warnings: RefCell<Vec<ParserWarning>>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(
+        source: &'a Source<'a>,
+        defines: &'a BTreeMap<String, i64>,
+        names: &'a ast::Aliases,
+        base_addr: u32,
+        data_addr: u32,
+        target: Option<Target>,
+        levels: BTreeMap<WarningKind, Level>,
+    ) -> Parser<'a> {
+        Parser {
+            source,
+            defines,
+            names,
+            dialect: source.dialect(),
+            input: source.lines().collect(),
+            line_num: 0,
+            addr: base_addr,
+            base_addr,
+            data_addr,
+            target,
+            levels,
+            labels: BTreeMap::new(),
+            local_labels: BTreeSet::new(),
+            constants: BTreeMap::new(),
+            aliases: BTreeMap::new(),
+            evaluating: RefCell::new(BTreeSet::new()),
+            scope: "",
+            function: false,
+            warnings: RefCell::new(vec![]),
+        }
+    }
+
+    pub fn parse(&mut self) -> Result<(Vec<Statement<'a>>, Vec<ParserWarning>), Vec<ParserError>> {
+        let mut errors = self.collect_symbols();
+        let mut statements = vec![];
+        let mut labels = vec![];
+        // Address each section continues at
+        let bases = [self.base_addr, self.data_addr];
+        let mut addrs = bases;
+        let mut section = Section::Text;
+        // Last label and the first statement crossing the end of IMEM or DMEM
+        // in each section
+        let mut symbols = [None, None];
+        let mut overflows = [None, None];
+        // Line of the previous instruction, if it has a delay slot
+        let mut delay_slot_of = None;
+
+        self.scope = "";
+        for i in 0..self.input.len() {
+            self.line_num = i + 1;
+            let (line_labels, stmt) = statement_labels(self.input[i]);
+            for label in line_labels {
+                if !label.starts_with('.') && !self.source.in_macro(self.line_num) {
+                    self.scope = label;
+                }
+                labels.push(label);
+                symbols[section as usize] = Some(label);
+            }
+            if stmt.is_empty() {
+                continue;
+            }
+            let addr = &mut addrs[section as usize];
+            self.addr = *addr;
+            if let Some(alias) = self.split_alias(stmt) {
+                if let Err(e) = alias.and_then(|(name, reg)| self.define_alias(name, reg)) {
+                    errors.push(e);
+                }
+                continue;
+            }
+            if let Some(constant) = self.split_constant(stmt) {
+                let value = constant.and_then(|(_, value)| {
+                    self.eval(value, false)?
+                        .ok_or_else(|| error!(self, InvalidImmediate, value))
+                });
+                if let Err(e) = value {
+                    errors.push(e);
+                }
+                continue;
+            }
+            let (mnemonic, ops) = split_statement(stmt);
+            let size = self.statement_size(mnemonic, ops);
+            let base = bases[section as usize];
+            if self.target != Some(Target::Vr4300)
+                && addr.wrapping_sub(base) + size > capacity(base)
+                && overflows[section as usize].is_none()
+            {
+                overflows[section as usize] =
+                    Some((self.line_num, mnemonic, symbols[section as usize]));
+            }
+            if let Some(directive) = directive(mnemonic) {
+                delay_slot_of = None;
+                if matches!(directive, Directive::Func | Directive::Endfunc) {
+                    // Aliases defined in a function end with it
+                    self.aliases.retain(|_, x| !x.local);
+                    self.function = directive == Directive::Func;
+                }
+                match self.parse_data(directive, ops, false) {
+                    Ok(_) if matches!(directive, Directive::Text | Directive::Data) => {
+                        // Labels before the switch stay in the section they are defined in
+                        if !labels.is_empty() {
+                            statements.push(Statement {
+                                labels: core::mem::take(&mut labels),
+                                mnemonic: "",
+                                ops: "",
+                                addr: *addr,
+                                section,
+                                insts: vec![],
+                                data: vec![],
+                            });
+                        }
+                        section = match directive {
+                            Directive::Text => Section::Text,
+                            _ => Section::Data,
+                        };
+                    }
+                    Ok(data) => {
+                        // Code is aligned with `nop`s instead of zero bytes
+                        let code = matches!(directive, Directive::Align | Directive::Balign)
+                            && section == Section::Text
+                            && *addr & 0x3 == 0
+                            && split_list(ops).len() == 1;
+                        let (insts, data) = match code {
+                            true => (vec![nop(); data.len() / 4], vec![]),
+                            false => (vec![], data),
+                        };
+                        statements.push(Statement {
+                            labels: core::mem::take(&mut labels),
+                            mnemonic,
+                            ops,
+                            addr: *addr,
+                            section,
+                            insts,
+                            data,
+                        });
+                    }
+                    Err(e) => errors.push(e),
+                }
+                *addr = addr.wrapping_add(size);
+                continue;
+            }
+            if *addr & 0x3 != 0 {
+                errors.push(error!(self, UnalignedInstruction, mnemonic, *addr));
+                *addr = addr.wrapping_add(size);
+                continue;
+            }
+            match self.parse_instruction(mnemonic, ops, *addr) {
+                Ok(insts) => {
+                    // A pseudoinstruction expanding to several instructions
+                    // only has its first one in the delay slot
+                    let in_delay_slot = insts.len() > 1 || insts[0].has_delay_slot();
+                    if let (true, Some(line_num)) = (in_delay_slot, delay_slot_of) {
+                        self.warn(warning!(self, InvalidInstructionInDelaySlot, line_num));
+                    }
+                    delay_slot_of = insts
+                        .last()
+                        .unwrap()
+                        .has_delay_slot()
+                        .then_some(self.line_num);
+                    statements.push(Statement {
+                        labels: core::mem::take(&mut labels),
+                        mnemonic,
+                        ops,
+                        addr: *addr,
+                        section,
+                        insts,
+                        data: vec![],
+                    });
+                }
+                Err(e) => {
+                    delay_slot_of = None;
+                    errors.push(e);
+                }
+            }
+            *addr = addr.wrapping_add(size);
+        }
+
+        for section in [Section::Text, Section::Data] {
+            let index = section as usize;
+            if let Some((line_num, mnemonic, symbol)) = overflows[index] {
+                let over = addrs[index].wrapping_sub(bases[index]) - capacity(bases[index]);
+                self.line_num = line_num;
+                errors.push(error!(
+                    self,
+                    SectionOverflow, mnemonic, section, symbol, over
+                ));
+            }
+        }
+
+        let mut warnings = vec![];
+        for warning in self.warnings.take() {
+            match self
+                .levels
+                .get(&warning.kind())
+                .copied()
+                .unwrap_or_default()
+            {
+                Level::Allow => {}
+                Level::Warn => warnings.push(warning),
+                Level::Deny => errors.push(ParserError::DeniedWarning(warning)),
+            }
+        }
+
+        if errors.is_empty() {
+            Ok((statements, warnings))
+        } else {
+            Err(errors)
+        }
+    }
+
+    // The line with the 1-based index `num`, for diagnostics
+    fn line(&self, num: usize) -> Line {
+        self.source.line(num)
+    }
+
+    fn warn(&self, warning: ParserWarning) {
+        self.warnings.borrow_mut().push(warning);
+    }
+
+    // First pass: assign an address to every label and record every constant
+    fn collect_symbols(&mut self) -> Vec<ParserError> {
+        let mut errors = vec![];
+        let mut addrs = [self.base_addr, self.data_addr];
+        let mut section = Section::Text;
+
+        for i in 0..self.input.len() {
+            self.line_num = i + 1;
+            let (labels, stmt) = statement_labels(self.input[i]);
+            let addr = &mut addrs[section as usize];
+            for label in labels {
+                let name = label.strip_prefix('.').unwrap_or(label);
+                if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+                    || !name.chars().all(is_label_char)
+                {
+                    errors.push(error!(self, InvalidLabel, label));
+                    continue;
+                }
+                let key = if label.starts_with('.') {
+                    self.local_labels.insert(label);
+                    format!("{}{}", self.scope, label)
+                } else {
+                    // Labels of macros, such as loops, stay in the scope they
+                    // are expanded in
+                    if !self.source.in_macro(self.line_num) {
+                        self.scope = label;
+                    }
+                    label.to_string()
+                };
+                if let Some(first) = self.first_definition(&key) {
+                    errors.push(error!(self, MultipleLabelDefinition, label, first));
+                    continue;
+                }
+                self.labels.insert(
+                    key,
+                    Label {
+                        addr: *addr,
+                        line: i,
+                    },
+                );
+            }
+            if stmt.is_empty() {
+                continue;
+            }
+            self.addr = *addr;
+            // Aliases are defined in the second pass, where their scope ends
+            if self.split_alias(stmt).is_some() {
+                continue;
+            }
+            // Malformed definitions are reported with the value in the second pass
+            if let Some(constant) = self.split_constant(stmt) {
+                if let Ok((name, value)) = constant {
+                    if let Err(e) = self.define_constant(name, value) {
+                        errors.push(e);
+                    }
+                }
+                continue;
+            }
+            let (mnemonic, ops) = split_statement(stmt);
+            *addr = addr.wrapping_add(self.statement_size(mnemonic, ops));
+            match directive(mnemonic) {
+                Some(Directive::Text) => section = Section::Text,
+                Some(Directive::Data) => section = Section::Data,
+                _ => {}
+            }
+        }
+        errors
+    }
+
+    // Splits a `.equ name, value`, `.set name, value` or `name = value`
+    // definition, `None` for any other statement
+    fn split_constant(&self, stmt: &'a str) -> Option<Result<(&'a str, &'a str), ParserError>> {
+        let (mnemonic, ops) = split_statement(stmt);
+        if mnemonic.eq_ignore_ascii_case(".equ") || mnemonic.eq_ignore_ascii_case(".set") {
+            return Some(self.operands(ops, 2).map(|o| (o[0], o[1])));
+        }
+        let index = assignment(stmt)?;
+        Some(Ok((stmt[..index].trim(), stmt[index + 1..].trim())))
+    }
+
+    fn define_constant(&mut self, name: &'a str, value: &'a str) -> Result<(), ParserError> {
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            || !name.chars().all(is_label_char)
+        {
+            return Err(error!(self, InvalidLabel, name));
+        }
+        if let Some(first) = self.first_definition(name) {
+            return Err(error!(self, MultipleConstantDefinition, name, first));
+        }
+        let constant = Constant {
+            value,
+            addr: self.addr,
+            scope: self.scope,
+            line: self.line_num - 1,
+        };
+        self.constants.insert(name, constant);
+        Ok(())
+    }
+
+    // Splits a `.alias name, reg` definition or a definition of a constant
+    // to a `$` register, `None` for any other statement
+    fn split_alias(&self, stmt: &'a str) -> Option<Result<(&'a str, &'a str), ParserError>> {
+        let (mnemonic, ops) = split_statement(stmt);
+        if mnemonic.eq_ignore_ascii_case(".alias") {
+            return Some(self.operands(ops, 2).map(|o| (o[0], o[1])));
+        }
+        match self.split_constant(stmt)? {
+            Ok((name, reg)) if reg.starts_with('$') => Some(Ok((name, reg))),
+            _ => None,
+        }
+    }
+
+    fn define_alias(&mut self, name: &'a str, reg: &'a str) -> Result<(), ParserError> {
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            || !name.chars().all(is_label_char)
+        {
+            return Err(error!(self, InvalidLabel, name));
+        }
+        if Reg::from_str(name).is_ok() || Vu::from_str(name).is_ok() {
+            return Err(error!(self, InvalidAliasName, name));
+        }
+        let first = match self.aliases.get(name) {
+            Some(alias) => Some(alias.line),
+            None => self.first_definition(name),
+        };
+        // Labels and constants are collected before aliases, so they can be
+        // defined after the alias they conflict with
+        if let Some(first) = first {
+            return Err(error!(self, MultipleAliasDefinition, name, first));
+        }
+        // An alias of an alias names the same register, a bare number is not
+        // taken as the register it numbers
+        let reg = self.aliases.get(reg).map_or(reg, |x| x.reg);
+        if reg.starts_with(|c: char| c.is_ascii_digit())
+            || Reg::from_str(reg).is_err() && Vu::from_str(reg).is_err()
+        {
+            return Err(error!(self, InvalidRegister, reg));
+        }
+        let alias = Alias {
+            reg,
+            local: self.function,
+            line: self.line_num - 1,
+        };
+        self.aliases.insert(name, alias);
+        Ok(())
+    }
+
+    // Line a label or constant is already defined on, labels and constants
+    // share their names
+    fn first_definition(&self, key: &str) -> Option<usize> {
+        match self.labels.get(key) {
+            Some(label) => Some(label.line),
+            None => self.constants.get(key).map(|x| x.line),
+        }
+    }
+
+    // Number of bytes a statement assembles to, known before the labels after
+    // it are resolved
+    fn statement_size(&self, mnemonic: &str, ops: &'a str) -> u32 {
+        if let Some(directive) = directive(mnemonic) {
+            return self
+                .parse_data(directive, ops, true)
+                .map_or(0, |x| x.len() as u32);
+        }
+        4 * self.statement_len(mnemonic, ops)
+    }
+
+    // Number of instructions a statement assembles to
+    fn statement_len(&self, mnemonic: &str, ops: &'a str) -> u32 {
+        match I::from_str(mnemonic) {
+            Ok(op @ (I::Li | I::Dli)) => {
+                let value = match split_operands(ops).get(1) {
+                    Some(value) => self.eval(value, true),
+                    None => return 1,
+                };
+                match value {
+                    Ok(Some(value)) => pseudo::load_immediate(op, Reg::null(), value).len() as u32,
+                    _ if op == I::Li => 2,
+                    _ => 6,
+                }
+            }
+            Ok(op) if op.is_pseudo() && op.is_branch() => match op {
+                I::B | I::Bal | I::Beqz | I::Bnez | I::Beqzl | I::Bnezl => 1,
+                _ => 2,
+            },
+            _ => 1,
+        }
+    }
+
+    fn parse_instruction(
+        &self,
+        mnemonic: &'a str,
+        ops: &'a str,
+        addr: u32,
+    ) -> Result<Vec<ast::Instruction>, ParserError> {
+        let insts = if let Some(inst) = self.parse_float_compare(mnemonic, ops) {
+            vec![inst?]
+        } else if let Ok(op) = I::from_str(mnemonic) {
+            if op.is_pseudo() {
+                self.parse_pseudo_inst(op, mnemonic, ops, addr)?
+            } else {
+                vec![self.parse_immediate_inst(op, mnemonic, ops, addr)?]
+            }
+        } else if let Ok(op) = J::from_str(mnemonic) {
+            vec![self.parse_jump_inst(op, ops, addr)?]
+        } else if let Ok(op) = R::from_str(mnemonic) {
+            if op.is_pseudo() {
+                vec![self.parse_pseudo_register_inst(op, mnemonic, ops)?]
+            } else {
+                vec![self.parse_register_inst(op, mnemonic, ops)?]
+            }
+        } else if let Ok(op) = V::from_str(mnemonic) {
+            vec![self.parse_vector_inst(op, ops)?]
+        } else if let Ok(op) = VM::from_str(mnemonic) {
+            vec![self.parse_vector_memory_inst(op, ops)?]
+        } else {
+            return Err(error!(self, InvalidOpcode, mnemonic));
+        };
+
+        match self.target {
+            Some(target) if !insts.iter().all(|x| target.supports(x)) => {
+                Err(error!(self, UnsupportedInstruction, mnemonic, target))
+            }
+            _ => Ok(insts),
+        }
+    }
+
+    fn parse_pseudo_inst(
+        &self,
+        op: ast::ITypeOp,
+        mnemonic: &'a str,
+        ops: &'a str,
+        addr: u32,
+    ) -> Result<Vec<ast::Instruction>, ParserError> {
+        let insts = match op {
+            I::B | I::Bal => {
+                let o = self.operands(ops, 1)?;
+                vec![pseudo::branch(
+                    op,
+                    Reg::null(),
+                    self.parse_branch(o[0], addr)?,
+                )]
+            }
+            I::Beqz | I::Bnez | I::Beqzl | I::Bnezl => {
+                let o = self.operands(ops, 2)?;
+                let rs = self.parse_gpr(o[0])?;
+                vec![pseudo::branch(op, rs, self.parse_branch(o[1], addr)?)]
+            }
+            I::Bge
+            | I::Bgt
+            | I::Ble
+            | I::Blt
+            | I::Bgeu
+            | I::Bgtu
+            | I::Bleu
+            | I::Bltu
+            | I::Bgel
+            | I::Bgtl
+            | I::Blel
+            | I::Bltl
+            | I::Bgeul
+            | I::Bgtul
+            | I::Bleul
+            | I::Bltul => {
+                let o = self.operands(ops, 3)?;
+                let (rs, rt) = (self.parse_gpr(o[0])?, self.parse_gpr(o[1])?);
+                let offset = self.parse_branch(o[2], addr.wrapping_add(4))?;
+                pseudo::compare_branch(op, rs, rt, offset)
+            }
+            I::Li | I::Dli | I::Lli => {
+                let o = self.operands(ops, 2)?;
+                let value = match op {
+                    I::Lli => self.parse_number(o[1], 0, 0xFFFF)?,
+                    I::Dli => self.parse_number(o[1], i64::MIN, i64::MAX)?,
+                    _ => self.parse_number(o[1], -0x8000_0000, 0xFFFF_FFFF)?,
+                };
+                let mut insts = pseudo::load_immediate(op, self.parse_gpr(o[0])?, value);
+                // A value using symbols defined later gets the longest expansion
+                insts.resize(self.statement_len(mnemonic, ops) as usize, nop());
+                insts
+            }
+            I::Subi | I::Subiu | I::Dsubi | I::Dsubiu => {
+                let o = self.operands(ops, 3)?;
+                let imm = self.parse_number(o[2], -0x7FFF, 0x8000)?;
+                let (rs, rt) = (self.parse_gpr(o[1])?, self.parse_gpr(o[0])?);
+                vec![pseudo::subtract_immediate(op, rs, rt, imm)]
+            }
+            _ => return Err(error!(self, InvalidOpcode, mnemonic)),
+        };
+        Ok(insts)
+    }
+
+    fn parse_immediate_inst(
+        &self,
+        op: ast::ITypeOp,
+        mnemonic: &'a str,
+        ops: &'a str,
+        addr: u32,
+    ) -> Result<ast::Instruction, ParserError> {
+        let (rs, rt, imm) = match op {
+            I::Lb
+            | I::Lbu
+            | I::Ld
+            | I::Ldl
+            | I::Ldr
+            | I::Lh
+            | I::Lhu
+            | I::Ll
+            | I::Lld
+            | I::Lw
+            | I::Lwl
+            | I::Lwr
+            | I::Lwu
+            | I::Sb
+            | I::Sc
+            | I::Scd
+            | I::Sd
+            | I::Sdl
+            | I::Sdr
+            | I::Sh
+            | I::Sw
+            | I::Swl
+            | I::Swr => {
+                let o = self.operands(ops, 2)?;
+                let (offset, base) = self.parse_memory(o[1], -0x8000, 0x7FFF)?;
+                (base, self.parse_gpr(o[0])?, offset as u16)
+            }
+            I::Ldc1 | I::Lwc1 | I::Sdc1 | I::Swc1 => {
+                let o = self.operands(ops, 2)?;
+                let (offset, base) = self.parse_memory(o[1], -0x8000, 0x7FFF)?;
+                (base, Reg::from(self.parse_fpr(o[0])?), offset as u16)
+            }
+            I::Cache => {
+                let o = self.operands(ops, 2)?;
+                let cache_op = self.parse_number(o[0], 0, 0x1F)?;
+                let (offset, base) = self.parse_memory(o[1], -0x8000, 0x7FFF)?;
+                (base, Reg::try_from(cache_op as u32).unwrap(), offset as u16)
+            }
+            I::Addi | I::Addiu | I::Daddi | I::Daddiu | I::Slti | I::Sltiu => {
+                let o = self.operands(ops, 3)?;
+                let imm = self.parse_number(o[2], -0x8000, 0x7FFF)?;
+                (self.parse_gpr(o[1])?, self.parse_gpr(o[0])?, imm as u16)
+            }
+            I::Andi | I::Ori | I::Xori => {
+                let o = self.operands(ops, 3)?;
+                // `%lo` is sign extended, but its 16 bits are the same unsigned
+                let min = if o[2].starts_with("%lo(") { -0x8000 } else { 0 };
+                let imm = self.parse_number(o[2], min, 0xFFFF)?;
+                (self.parse_gpr(o[1])?, self.parse_gpr(o[0])?, imm as u16)
+            }
+            I::Lui => {
+                let o = self.operands(ops, 2)?;
+                let imm = self.parse_number(o[1], 0, 0xFFFF)?;
+                (Reg::null(), self.parse_gpr(o[0])?, imm as u16)
+            }
+            I::Beq | I::Beql | I::Bne | I::Bnel => {
+                let o = self.operands(ops, 3)?;
+                let offset = self.parse_branch(o[2], addr)?;
+                (self.parse_gpr(o[0])?, self.parse_gpr(o[1])?, offset)
+            }
+            I::Bgtz
+            | I::Bgtzl
+            | I::Blez
+            | I::Blezl
+            | I::Bgez
+            | I::Bgezal
+            | I::Bgezall
+            | I::Bgezl
+            | I::Bltz
+            | I::Bltzal
+            | I::Bltzall
+            | I::Bltzl => {
+                let o = self.operands(ops, 2)?;
+                let offset = self.parse_branch(o[1], addr)?;
+                (self.parse_gpr(o[0])?, Reg::null(), offset)
+            }
+            I::Teqi | I::Tgei | I::Tgeiu | I::Tlti | I::Tltiu | I::Tnei => {
+                let o = self.operands(ops, 2)?;
+                let imm = self.parse_number(o[1], -0x8000, 0x7FFF)?;
+                (self.parse_gpr(o[0])?, Reg::null(), imm as u16)
+            }
+            I::Bc0f | I::Bc0fl | I::Bc0t | I::Bc0tl | I::Bc1f | I::Bc1fl | I::Bc1t | I::Bc1tl => {
+                let o = self.operands(ops, 1)?;
+                (Reg::null(), Reg::null(), self.parse_branch(o[0], addr)?)
+            }
+            _ => return Err(error!(self, InvalidOpcode, mnemonic)),
+        };
+
+        Ok(ast::Instruction::Immediate {
+            op,
+            rs,
+            rt,
+            imm: ast::Immediate(imm),
+        })
+    }
+
+    fn parse_pseudo_register_inst(
+        &self,
+        op: ast::RTypeOp,
+        mnemonic: &'a str,
+        ops: &'a str,
+    ) -> Result<ast::Instruction, ParserError> {
+        match op {
+            R::Nop => {
+                self.operands(ops, 0)?;
+                Ok(pseudo::register_op(op, Reg::null(), Reg::null()))
+            }
+            R::Move | R::Neg | R::Negu | R::Not => {
+                let o = self.operands(ops, 2)?;
+                let (rd, rs) = (self.parse_gpr(o[0])?, self.parse_gpr(o[1])?);
+                Ok(pseudo::register_op(op, rd, rs))
+            }
+            _ => Err(error!(self, InvalidOpcode, mnemonic)),
+        }
+    }
+
+    fn parse_jump_inst(
+        &self,
+        op: ast::JTypeOp,
+        ops: &'a str,
+        addr: u32,
+    ) -> Result<ast::Instruction, ParserError> {
+        let o = self.operands(ops, 1)?;
+        let target = self.parse_address(o[0])?;
+        if target & 0xF000_0000 != addr.wrapping_add(4) & 0xF000_0000 {
+            return Err(error!(self, InvalidTargetAddress, o[0]));
+        }
+        if target & 0x3 != 0 {
+            self.warn(warning!(self, UnalignedJump, o[0]));
+        }
+        Ok(ast::Instruction::Jump {
+            op,
+            target: ast::Target(target & 0x0FFF_FFFF),
+        })
+    }
+
+    fn parse_register_inst(
+        &self,
+        op: ast::RTypeOp,
+        mnemonic: &'a str,
+        ops: &'a str,
+    ) -> Result<ast::Instruction, ParserError> {
+        let (rs, rt, rd, sa) = match op {
+            R::Add
+            | R::Addu
+            | R::And
+            | R::Dadd
+            | R::Daddu
+            | R::Dsub
+            | R::Dsubu
+            | R::Nor
+            | R::Or
+            | R::Slt
+            | R::Sltu
+            | R::Sub
+            | R::Subu
+            | R::Xor => {
+                let o = self.operands(ops, 3)?;
+                let rd = self.parse_gpr(o[0])?;
+                (self.parse_gpr(o[1])?, self.parse_gpr(o[2])?, rd, 0)
+            }
+            R::Dsll
+            | R::Dsll32
+            | R::Dsra
+            | R::Dsra32
+            | R::Dsrl
+            | R::Dsrl32
+            | R::Sll
+            | R::Sra
+            | R::Srl => {
+                let o = self.operands(ops, 3)?;
+                let sa = self.parse_number(o[2], 0, 0x1F)?;
+                let rd = self.parse_gpr(o[0])?;
+                (Reg::null(), self.parse_gpr(o[1])?, rd, sa as u32)
+            }
+            R::Dsllv | R::Dsrav | R::Dsrlv | R::Sllv | R::Srav | R::Srlv => {
+                let o = self.operands(ops, 3)?;
+                let rd = self.parse_gpr(o[0])?;
+                (self.parse_gpr(o[2])?, self.parse_gpr(o[1])?, rd, 0)
+            }
+            R::Break | R::Syscall => {
+                let code = match split_operands(ops).as_slice() {
+                    [] => 0,
+                    [code] => self.parse_number(code, 0, 0xFFFFF)? as u32,
+                    o => return Err(error!(self, InvalidOperandCount, ops, 1, o.len())),
+                };
+                (Reg::null(), Reg::null(), Reg::null(), code)
+            }
+            R::Ddiv
+            | R::Ddivu
+            | R::Div
+            | R::Divu
+            | R::Dmult
+            | R::Dmultu
+            | R::Mult
+            | R::Multu
+            | R::Teq
+            | R::Tge
+            | R::Tgeu
+            | R::Tlt
+            | R::Tltu
+            | R::Tne => {
+                let o = self.operands(ops, 2)?;
+                let rs = self.parse_gpr(o[0])?;
+                (rs, self.parse_gpr(o[1])?, Reg::null(), 0)
+            }
+            R::Jalr => match split_operands(ops).as_slice() {
+                [rs] => (self.parse_gpr(rs)?, Reg::null(), Reg::Ra, 0),
+                [rd, rs] => {
+                    let rd = self.parse_gpr(rd)?;
+                    (self.parse_gpr(rs)?, Reg::null(), rd, 0)
+                }
+                o => return Err(error!(self, InvalidOperandCount, ops, 2, o.len())),
+            },
+            R::Jr | R::Mthi | R::Mtlo => {
+                let o = self.operands(ops, 1)?;
+                (self.parse_gpr(o[0])?, Reg::null(), Reg::null(), 0)
+            }
+            R::Mfhi | R::Mflo => {
+                let o = self.operands(ops, 1)?;
+                (Reg::null(), Reg::null(), self.parse_gpr(o[0])?, 0)
+            }
+            R::Sync | R::Eret | R::Tlbp | R::Tlbr | R::Tlbwi | R::Tlbwr => {
+                self.operands(ops, 0)?;
+                (Reg::null(), Reg::null(), Reg::null(), 0)
+            }
+            R::Cfc0 | R::Ctc0 | R::Dmfc0 | R::Dmtc0 | R::Mfc0 | R::Mtc0 => {
+                let o = self.operands(ops, 2)?;
+                let rt = self.parse_gpr(o[0])?;
+                (Reg::null(), rt, self.parse_cop0(o[1])?, 0)
+            }
+            R::Cfc1 | R::Ctc1 | R::Dmfc1 | R::Dmtc1 | R::Mfc1 | R::Mtc1 => {
+                let o = self.operands(ops, 2)?;
+                let rt = self.parse_gpr(o[0])?;
+                (Reg::null(), rt, Reg::from(self.parse_fpr(o[1])?), 0)
+            }
+            R::Mfc2 | R::Mtc2 => {
+                let o = self.vector_operands(ops, 2)?;
+                let rt = self.parse_gpr(o[0])?;
+                let (vs, e) = self.parse_vreg_element(o[1], Element::Byte)?;
+                (Reg::null(), rt, Reg::from(vs), e)
+            }
+            R::Cfc2 | R::Ctc2 => {
+                let o = self.operands(ops, 2)?;
+                let rt = self.parse_gpr(o[0])?;
+                let rd = ast::VuControlRegister::from_str(o[1])
+                    .map_err(|_| error!(self, InvalidRegister, o[1]))?;
+                (Reg::null(), rt, Reg::from(rd), 0)
+            }
+            R::AddS | R::AddD | R::SubS | R::SubD | R::MulS | R::MulD | R::DivS | R::DivD => {
+                let o = self.operands(ops, 3)?;
+                let rd = Reg::from(self.parse_fpr(o[0])?);
+                let rs = Reg::from(self.parse_fpr(o[1])?);
+                (rs, Reg::from(self.parse_fpr(o[2])?), rd, 0)
+            }
+            R::AbsS
+            | R::AbsD
+            | R::CvtDS
+            | R::CvtDW
+            | R::CvtDL
+            | R::CvtLS
+            | R::CvtLD
+            | R::CvtSD
+            | R::CvtSW
+            | R::CvtSL
+            | R::CvtWD
+            | R::CvtWS
+            | R::MovS
+            | R::MovD
+            | R::NegS
+            | R::NegD
+            | R::SqrtS
+            | R::SqrtD
+            | R::CeilLS
+            | R::CeilLD
+            | R::CeilWS
+            | R::CeilWD
+            | R::FloorLS
+            | R::FloorLD
+            | R::FloorWS
+            | R::FloorWD
+            | R::RoundLS
+            | R::RoundLD
+            | R::RoundWS
+            | R::RoundWD
+            | R::TruncLS
+            | R::TruncLD
+            | R::TruncWS
+            | R::TruncWD => {
+                let o = self.operands(ops, 2)?;
+                let rd = Reg::from(self.parse_fpr(o[0])?);
+                (Reg::from(self.parse_fpr(o[1])?), Reg::null(), rd, 0)
+            }
+            _ => return Err(error!(self, InvalidOpcode, mnemonic)),
+        };
+
+        Ok(ast::Instruction::Register { op, rs, rt, rd, sa })
+    }
+
+    // c.cond.fmt is not a single opcode, the condition is encoded in the instruction
+    fn parse_float_compare(
+        &self,
+        mnemonic: &'a str,
+        ops: &'a str,
+    ) -> Option<Result<ast::Instruction, ParserError>> {
+        let mut parts = mnemonic.split('.');
+        let (c, cond, fmt) = (parts.next()?, parts.next()?, parts.next()?);
+        if !c.eq_ignore_ascii_case("c") || parts.next().is_some() {
+            return None;
+        }
+
+        let parse = || {
+            let op = match fmt.to_lowercase().as_str() {
+                "s" => R::Cs,
+                "d" => R::Cd,
+                _ => return Err(error!(self, InvalidOpcode, mnemonic)),
+            };
+            let cond =
+                ast::FloatCond::from_str(cond).map_err(|_| error!(self, InvalidFloatCond, cond))?;
+            let o = self.operands(ops, 2)?;
+            let rs = Reg::from(self.parse_fpr(o[0])?);
+            Ok(ast::Instruction::Register {
+                op,
+                rs,
+                rt: Reg::from(self.parse_fpr(o[1])?),
+                rd: Reg::null(),
+                sa: cond as u32,
+            })
+        };
+        Some(parse())
+    }
+
+    fn parse_vector_inst(
+        &self,
+        op: ast::VTypeOp,
+        ops: &'a str,
+    ) -> Result<ast::Instruction, ParserError> {
+        let (vd, vs, vt, de, e) = match op {
+            V::Vnop => {
+                self.operands(ops, 0)?;
+                (Vu::null(), Vu::null(), Vu::null(), 0, 0)
+            }
+            V::Vrsq | V::Vrsqh | V::Vrsql | V::Vrcp | V::Vrcph | V::Vrcpl => {
+                let o = self.vector_operands(ops, 2)?;
+                let (vd, de) = self.parse_vreg_element(o[0], Element::Lane)?;
+                let (vt, e) = self.parse_vreg_element(o[1], Element::Broadcast)?;
+                (vd, Vu::null(), vt, de, e)
+            }
+            _ => {
+                let o = self.vector_operands(ops, 3)?;
+                let vd = self.parse_vreg(o[0])?;
+                let vs = self.parse_vreg(o[1])?;
+                let (vt, e) = self.parse_vreg_element(o[2], Element::Broadcast)?;
+                (vd, vs, vt, 0, e)
+            }
+        };
+
+        Ok(ast::Instruction::Vector {
+            op,
+            vd,
+            vs,
+            vt,
+            de,
+            e,
+        })
+    }
+
+    fn parse_vector_memory_inst(
+        &self,
+        op: ast::VMemTypeOp,
+        ops: &'a str,
+    ) -> Result<ast::Instruction, ParserError> {
+        // GNU as also takes `vt.e, offset, base` and `vt, e, offset, base`
+        let o = match split_vector_operands(ops) {
+            o if self.dialect == Dialect::Gnu && matches!(o.len(), 3 | 4) => o,
+            _ => self.vector_operands(ops, 2)?,
+        };
+        let scale = op.scale() as i64;
+        let (min, max) = (-0x40 * scale, 0x3F * scale);
+        let ((vt, e), (offset, base), written) = match *o.as_slice() {
+            [vt, memory] => (
+                self.parse_vreg_element(vt, Element::Byte)?,
+                self.parse_memory(memory, min, max)?,
+                memory,
+            ),
+            [vt, offset, base] => (
+                self.parse_vreg_element(vt, Element::Byte)?,
+                (self.parse_number(offset, min, max)?, self.parse_gpr(base)?),
+                offset,
+            ),
+            [vt, e, offset, base] => (
+                (self.parse_vreg(vt)?, self.parse_element(e, Element::Byte)?),
+                (self.parse_number(offset, min, max)?, self.parse_gpr(base)?),
+                offset,
+            ),
+            _ => unreachable!(),
+        };
+        if offset % scale != 0 {
+            return Err(error!(self, InvalidImmediate, written));
+        }
+
+        Ok(ast::Instruction::VectorMemory {
+            op,
+            vt,
+            e,
+            offset: (offset / scale) as u32 & 0x7F,
+            base,
+        })
+    }
+
+    // The bytes of a data directive. Only the sizes are known in `layout`, the
+    // values are left as zeros.
+    fn parse_data(
+        &self,
+        directive: Directive,
+        ops: &'a str,
+        layout: bool,
+    ) -> Result<Vec<u8>, ParserError> {
+        let o = split_list(ops);
+        let mut data = vec![];
+        match directive {
+            Directive::Byte | Directive::Half | Directive::Word | Directive::Dword => {
+                let (width, min, max) = match directive {
+                    Directive::Byte => (1, -0x80, 0xFF),
+                    Directive::Half => (2, -0x8000, 0xFFFF),
+                    Directive::Word => (4, -0x8000_0000, 0xFFFF_FFFF),
+                    _ => (8, i64::MIN, i64::MAX),
+                };
+                if o.is_empty() {
+                    return Err(error!(self, InvalidOperandCount, ops, 1, 0));
+                }
+                for value in o {
+                    // `.byte` also takes strings, as `.db` does in armips and bass
+                    if directive == Directive::Byte && value.starts_with('"') {
+                        let s = parse_string(value)
+                            .ok_or_else(|| error!(self, InvalidString, value))?;
+                        data.extend(s);
+                        continue;
+                    }
+                    let value = match layout {
+                        true => 0,
+                        false => self.parse_number(value, min, max)?,
+                    };
+                    data.extend_from_slice(&value.to_be_bytes()[8 - width..]);
+                }
+            }
+            Directive::Ascii | Directive::Asciiz => {
+                if o.is_empty() {
+                    return Err(error!(self, InvalidOperandCount, ops, 1, 0));
+                }
+                for s in o {
+                    data.extend(parse_string(s).ok_or_else(|| error!(self, InvalidString, s))?);
+                    if directive == Directive::Asciiz {
+                        data.push(0);
+                    }
+                }
+            }
+            // `.space size, fill`
+            Directive::Space => {
+                let (size, fill) = match o.as_slice() {
+                    [size] => (self.parse_size(size)?, 0),
+                    [size, fill] => (self.parse_size(size)?, self.parse_fill(fill, 1, layout)?),
+                    _ => return Err(error!(self, InvalidOperandCount, ops, 2, o.len())),
+                };
+                data.resize(size, fill as u8);
+            }
+            // `.fill repeat, size, value` with a size of up to 8 bytes
+            Directive::Fill => {
+                let (repeat, width, value) = match o.as_slice() {
+                    [repeat] => (self.parse_size(repeat)?, 1, 0),
+                    [repeat, width] => (self.parse_size(repeat)?, self.parse_width(width)?, 0),
+                    [repeat, width, value] => {
+                        let width = self.parse_width(width)?;
+                        (
+                            self.parse_size(repeat)?,
+                            width,
+                            self.parse_fill(value, width, layout)?,
+                        )
+                    }
+                    _ => return Err(error!(self, InvalidOperandCount, ops, 3, o.len())),
+                };
+                for _ in 0..repeat {
+                    data.extend_from_slice(&value.to_be_bytes()[8 - width..]);
+                }
+            }
+            // `.incbin "path", offset, size` with the file read while expanding the source
+            Directive::Incbin => {
+                let binary = self.source.binary(self.line_num);
+                let (offset, size) = match o.as_slice() {
+                    [_] => (0, binary.len()),
+                    [_, offset] => {
+                        let offset = self.parse_size(offset)?;
+                        (offset, binary.len().saturating_sub(offset))
+                    }
+                    [_, offset, size] => (self.parse_size(offset)?, self.parse_size(size)?),
+                    _ => return Err(error!(self, InvalidOperandCount, ops, 3, o.len())),
+                };
+                match binary.get(offset..offset + size) {
+                    Some(bytes) => data.extend_from_slice(bytes),
+                    None => return Err(error!(self, InvalidImmediate, o[o.len() - 1])),
+                }
+            }
+            Directive::Text | Directive::Data | Directive::Endfunc => {
+                self.operands(ops, 0)?;
+            }
+            // `.func name` defines the label `name`
+            Directive::Func => {
+                self.operands(ops, 1)?;
+            }
+            // `.align n, fill` pads to a multiple of 2^n bytes
+            Directive::Align => {
+                let (n, fill) = match o.as_slice() {
+                    [n] => (self.parse_count(n, 16)?, 0),
+                    [n, fill] => (self.parse_count(n, 16)?, self.parse_fill(fill, 1, layout)?),
+                    _ => return Err(error!(self, InvalidOperandCount, ops, 2, o.len())),
+                };
+                let align = 1 << n;
+                let pad = self.addr.wrapping_neg() % align;
+                data.resize(pad as usize, fill as u8);
+            }
+            // `.balign n, fill` pads to a multiple of n bytes, a power of two
+            Directive::Balign => {
+                let (n, fill) = match o.as_slice() {
+                    [n] => (self.parse_count(n, 0x1_0000)?, 0),
+                    [n, fill] => (
+                        self.parse_count(n, 0x1_0000)?,
+                        self.parse_fill(fill, 1, layout)?,
+                    ),
+                    _ => return Err(error!(self, InvalidOperandCount, ops, 2, o.len())),
+                };
+                if !n.is_power_of_two() {
+                    return Err(error!(self, InvalidImmediate, o[0]));
+                }
+                let pad = self.addr.wrapping_neg() % n;
+                data.resize(pad as usize, fill as u8);
+            }
+        }
+        Ok(data)
+    }
+
+    // Counts decide the layout, so they can only use the symbols defined before them
+    fn parse_count(&self, s: &'a str, max: i64) -> Result<u32, ParserError> {
+        match self.eval(s, true)? {
+            Some(x) if (0..=max).contains(&x) => Ok(x as u32),
+            _ => Err(error!(self, InvalidImmediate, s)),
+        }
+    }
+
+    fn parse_size(&self, s: &'a str) -> Result<usize, ParserError> {
+        self.parse_count(s, 0xFF_FFFF).map(|x| x as usize)
+    }
+
+    fn parse_width(&self, s: &'a str) -> Result<usize, ParserError> {
+        match self.parse_count(s, 8)? {
+            0 => Err(error!(self, InvalidImmediate, s)),
+            x => Ok(x as usize),
+        }
+    }
+
+    // A value of `width` bytes to fill with, unknown while laying out
+    fn parse_fill(&self, s: &'a str, width: usize, layout: bool) -> Result<i64, ParserError> {
+        if layout {
+            return Ok(0);
+        }
+        match width {
+            8 => self.parse_number(s, i64::MIN, i64::MAX),
+            _ => self.parse_number(s, -(1 << (8 * width - 1)), (1 << (8 * width)) - 1),
+        }
+    }
+
+    fn operands(&self, ops: &'a str, expected: usize) -> Result<Vec<&'a str>, ParserError> {
+        // A single operand may be an expression with spaces in it
+        let operands = match expected {
+            1 if !ops.is_empty() && !ops.contains(',') => vec![ops],
+            _ => split_operands(ops),
+        };
+        if operands.len() != expected {
+            return Err(error!(
+                self,
+                InvalidOperandCount,
+                ops,
+                expected,
+                operands.len()
+            ));
+        }
+        Ok(operands)
+    }
+
+    // Like `operands`, but keeps `vt,e(N)` together as one operand
+    fn vector_operands(&self, ops: &'a str, expected: usize) -> Result<Vec<&'a str>, ParserError> {
+        let operands = split_vector_operands(ops);
+        if operands.len() != expected {
+            return Err(error!(
+                self,
+                InvalidOperandCount,
+                ops,
+                expected,
+                operands.len()
+            ));
+        }
+        Ok(operands)
+    }
+
+    // Aliases in the input replace the ones defined outside of it
+    fn parse_gpr(&self, reg: &'a str) -> Result<ast::Register, ParserError> {
+        match self.aliases.get(reg) {
+            Some(alias) => Reg::from_str(alias.reg).ok(),
+            None if !self.dialect.register_prefix(reg) => None,
+            None => Reg::from_str(reg)
+                .ok()
+                .or_else(|| self.names.find_register(reg)),
+        }
+        .ok_or_else(|| error!(self, InvalidRegister, reg))
+    }
+
+    fn parse_fpr(&self, reg: &'a str) -> Result<ast::FloatRegister, ParserError> {
+        ast::FloatRegister::from_str(reg).map_err(|_| error!(self, InvalidRegister, reg))
+    }
+
+    // Registers without a name on the RSP are still addressable by number
+    fn parse_cop0(&self, reg: &'a str) -> Result<ast::Register, ParserError> {
+        ast::Cop0Register::from_str(reg)
+            .map(Reg::from)
+            .or_else(|_| Reg::try_from(reg.trim_start_matches('$').parse::<u32>().unwrap_or(32)))
+            .or_else(|e| self.dialect.cop0(reg).map_or(Err(e), Reg::try_from))
+            .map_err(|_| error!(self, InvalidRegister, reg))
+    }
+
+    fn parse_vreg(&self, reg: &'a str) -> Result<ast::VuRegister, ParserError> {
+        match self.aliases.get(reg) {
+            Some(alias) => Vu::from_str(alias.reg).ok(),
+            None if !self.dialect.vu_register_prefix(reg) => None,
+            None => Vu::from_str(reg)
+                .ok()
+                .or_else(|| self.names.find_vu_register(reg)),
+        }
+        .ok_or_else(|| error!(self, InvalidRegister, reg))
+    }
+
+    // Parses `vt[e]`, `vt.e` or `vt,e(N)`, a missing element selects the
+    // whole vector
+    fn parse_vreg_element(
+        &self,
+        s: &'a str,
+        element: Element,
+    ) -> Result<(ast::VuRegister, u32), ParserError> {
+        let (reg, e) = split_element(s);
+        let vt = self.parse_vreg(reg)?;
+        match e {
+            Some(e) => Ok((vt, self.parse_element(e, element)?)),
+            None => Ok((vt, 0)),
+        }
+    }
+
+    fn parse_element(&self, s: &'a str, element: Element) -> Result<u32, ParserError> {
+        let invalid = || error!(self, InvalidElement, s, element);
+        let (selector, n) = match selector(s) {
+            // bass writes the encoded element as `eN`
+            Some((Selector::Lane, n)) if self.dialect == Dialect::Bass => (None, n.parse().ok()),
+            Some((selector, n)) => (Some(selector), n.parse().ok()),
+            None => (None, self.eval(s, false).ok().flatten()),
+        };
+        let n: i64 = n.ok_or_else(invalid)?;
+        let max = match (element, selector) {
+            // armips selects halfword N with a number
+            (Element::Broadcast, None) if self.dialect == Dialect::Armips => 7,
+            (Element::Lane, None) => 0x1F,
+            (_, None) => 0xF,
+            _ => 7,
+        };
+        if !(0..=max).contains(&n) {
+            return Err(invalid());
+        }
+        let n = n as u32;
+        match (element, selector) {
+            (Element::Broadcast, None) if self.dialect == Dialect::Armips => Ok(8 + n),
+            (_, None) => Ok(n),
+            (Element::Broadcast, Some(Selector::Lane)) => Ok(8 + n),
+            (Element::Broadcast, Some(Selector::Quarter)) => Ok(2 + n % 2),
+            (Element::Broadcast, Some(Selector::Half)) => Ok(4 + n % 4),
+            (Element::Lane, Some(Selector::Lane)) => Ok(n),
+            (Element::Byte, Some(Selector::Lane)) => Ok(2 * n),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn parse_number(&self, s: &'a str, min: i64, max: i64) -> Result<i64, ParserError> {
+        match self.eval(s, false)? {
+            Some(x) if (min..=max).contains(&x) => Ok(x),
+            _ => Err(error!(self, InvalidImmediate, s)),
+        }
+    }
+
+    // Evaluates an expression, only seeing the symbols defined before the
+    // statement when `layout` is set, as while the addresses are assigned
+    fn eval(&self, s: &'a str, layout: bool) -> Result<Option<i64>, ParserError> {
+        self.eval_at(s, self.addr, self.scope, layout)
+    }
+
+    // Evaluates an expression as if it was written at `addr` after the label `scope`
+    fn eval_at(
+        &self,
+        s: &'a str,
+        addr: u32,
+        scope: &'a str,
+        layout: bool,
+    ) -> Result<Option<i64>, ParserError> {
+        expr::eval(s, &|name| self.symbol(name, addr, scope, layout))
+    }
+
+    fn symbol(
+        &self,
+        name: &'a str,
+        addr: u32,
+        scope: &'a str,
+        layout: bool,
+    ) -> Result<i64, ParserError> {
+        if name == "." {
+            return Ok(addr as i64);
+        }
+        if let Some(constant) = self.constants.get(name) {
+            if !layout || constant.line < self.line_num {
+                return self.constant(name, constant, layout);
+            }
+        }
+        let key = if name.starts_with('.') {
+            format!("{}{}", scope, name)
+        } else {
+            name.to_string()
+        };
+        match self.labels.get(&key) {
+            Some(label) if !layout || label.line < self.line_num => Ok(label.addr as i64),
+            None if self.local_labels.contains(name) => {
+                Err(error!(self, LocalLabelOutOfScope, self.line_num, name))
+            }
+            // Constants in the input replace the defines of the same name
+            None if !self.constants.contains_key(name) && self.defines.contains_key(name) => {
+                Ok(self.defines[name])
+            }
+            _ => Err(error!(self, UndefinedLabel, self.line_num, name)),
+        }
+    }
+
+    // The errors in the value of a constant are reported where it is defined,
+    // a use only reports that the value is invalid
+    fn constant(
+        &self,
+        name: &'a str,
+        constant: &Constant<'a>,
+        layout: bool,
+    ) -> Result<i64, ParserError> {
+        if !self.evaluating.borrow_mut().insert(name) {
+            return Err(error!(self, InvalidConstant, name));
+        }
+        let value = self.eval_at(constant.value, constant.addr, constant.scope, layout);
+        self.evaluating.borrow_mut().remove(name);
+        match value {
+            Ok(Some(x)) => Ok(x),
+            _ => Err(error!(self, InvalidConstant, name)),
+        }
+    }
+
+    // Parses `offset(base)`
+    fn parse_memory(
+        &self,
+        s: &'a str,
+        min: i64,
+        max: i64,
+    ) -> Result<(i64, ast::Register), ParserError> {
+        let (offset, base) = match s.strip_suffix(')').and_then(|s| s.rsplit_once('(')) {
+            Some(x) => x,
+            None => return Err(error!(self, InvalidInstruction)),
+        };
+        let offset = match offset.trim() {
+            "" => 0,
+            offset => self.parse_number(offset, min, max)?,
+        };
+        Ok((offset, self.parse_gpr(base.trim())?))
+    }
+
+    fn parse_address(&self, s: &'a str) -> Result<u32, ParserError> {
+        self.eval(s, false)?
+            .and_then(|x| u32::try_from(x).ok())
+            .ok_or_else(|| error!(self, InvalidTargetAddress, s))
+    }
+
+    // Branch offsets are counted in words from the delay slot
+    fn parse_branch(&self, s: &'a str, addr: u32) -> Result<u16, ParserError> {
+        let target = self.parse_address(s)?;
+        let pc = addr.wrapping_add(4);
+        if target & 0x3 != 0 {
+            self.warn(warning!(self, UnalignedBranch, s));
+        }
+        let offset = target.wrapping_sub(pc) as i32 >> 2;
+        if !(-0x8000..=0x7FFF).contains(&offset) {
+            let bounds = (pc.wrapping_sub(0x20000), pc.wrapping_add(0x1FFFC));
+            return Err(error!(
+                self,
+                BranchOutOfBounds,
+                self.line_num,
+                s.to_string(),
+                bounds
+            ));
+        }
+        Ok(offset as u16)
+    }
+}
+
+fn nop() -> ast::Instruction {
+    pseudo::register_op(R::Nop, Reg::null(), Reg::null())
+}
+
+fn directive(mnemonic: &str) -> Option<Directive> {
+    Directive::from_str(mnemonic.strip_prefix('.')?).ok()
+}
+
+// Splits the labels before a statement, including the name of a `.func`
+fn statement_labels(line: &str) -> (Vec<&str>, &str) {
+    let (mut labels, stmt) = split_labels(line);
+    let (mnemonic, ops) = split_statement(stmt);
+    if directive(mnemonic) == Some(Directive::Func) && !ops.is_empty() {
+        labels.push(ops);
+    }
+    (labels, stmt)
+}
+
+pub fn split_statement(stmt: &str) -> (&str, &str) {
+    match stmt.split_once(char::is_whitespace) {
+        Some((mnemonic, ops)) => (mnemonic, ops.trim()),
+        None => (stmt, ""),
+    }
+}
+
+fn strip_comment(line: &str) -> &str {
+    let end = unquoted(line)
+        .find(|&(i, _)| ["#", ";", "//"].iter().any(|c| line[i..].starts_with(c)))
+        .map_or(line.len(), |(i, _)| i);
+    &line[..end]
+}
+
+// Byte offset of the `=` in a `name = value` definition, skipping the ones
+// in comparisons
+pub fn assignment(stmt: &str) -> Option<usize> {
+    let bytes = stmt.as_bytes();
+    unquoted(stmt).map(|(i, _)| i).find(|&i| {
+        bytes[i] == b'='
+            && bytes.get(i + 1) != Some(&b'=')
+            && !matches!(
+                i.checked_sub(1).map(|j| bytes[j]),
+                Some(b'=' | b'!' | b'<' | b'>')
+            )
+    })
+}
+
+// The characters of `s` outside of string literals and their byte offsets
+pub fn unquoted(s: &str) -> impl Iterator<Item = (usize, char)> + '_ {
+    let (mut quoted, mut escaped) = (false, false);
+    s.char_indices().filter(move |&(_, c)| {
+        let outside = !quoted && c != '"';
+        if escaped {
+            escaped = false;
+        } else if quoted && c == '\\' {
+            escaped = true;
+        } else if c == '"' {
+            quoted = !quoted;
+        }
+        outside
+    })
+}
+
+// Parses a string literal with C escapes
+pub fn parse_string(s: &str) -> Option<Vec<u8>> {
+    let s = s.strip_prefix('"')?.strip_suffix('"')?;
+    let mut bytes = vec![];
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '"' => return None,
+            '\\' => match chars.next()? {
+                'n' => '\n',
+                't' => '\t',
+                'r' => '\r',
+                '0' => '\0',
+                'x' => {
+                    let hex = chars.as_str().get(..2)?;
+                    bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                    chars = chars.as_str()[2..].chars();
+                    continue;
+                }
+                c @ ('\\' | '"' | '\'') => c,
+                _ => return None,
+            },
+            c => c,
+        };
+        let mut buf = [0; 4];
+        bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+    }
+    Some(bytes)
+}
+
+// Splits any leading `label:` definitions from the statement on a line
+pub fn split_labels(line: &str) -> (Vec<&str>, &str) {
+    let mut labels = vec![];
+    let mut rest = strip_comment(line).trim();
+    while let Some((label, stmt)) = rest.split_once(':') {
+        let label = label.trim();
+        if label.is_empty() || label.contains(char::is_whitespace) {
+            break;
+        }
+        labels.push(label);
+        rest = stmt.trim();
+    }
+    (labels, rest)
+}
+
+pub fn split_operands(ops: &str) -> Vec<&str> {
+    if unquoted(ops).any(|(_, c)| c == ',') {
+        split_list(ops)
+    } else {
+        ops.split_whitespace().collect()
+    }
+}
+
+// Splits comma separated operands, which may be strings containing commas
+pub fn split_list(ops: &str) -> Vec<&str> {
+    if ops.is_empty() {
+        return vec![];
+    }
+    let mut operands = vec![];
+    let mut start = 0;
+    for (i, _) in unquoted(ops).filter(|&(_, c)| c == ',') {
+        operands.push(ops[start..i].trim());
+        start = i + 1;
+    }
+    operands.push(ops[start..].trim());
+    operands
+}
+
+// Splits the operands of a vector instruction, an `e(N)` element is kept with
+// the register before it
+pub fn split_vector_operands(ops: &str) -> Vec<&str> {
+    if !unquoted(ops).any(|(_, c)| c == ',') {
+        return split_operands(ops);
+    }
+    let mut operands = vec![];
+    let mut start = 0;
+    for (i, _) in unquoted(ops).filter(|&(_, c)| c == ',') {
+        let next = ops[i + 1..].trim_start();
+        if next.get(..2).is_some_and(|x| x.eq_ignore_ascii_case("e(")) {
+            continue;
+        }
+        operands.push(ops[start..i].trim());
+        start = i + 1;
+    }
+    operands.push(ops[start..].trim());
+    operands
+}
+
+// Splits `vt[e]`, `vt.e` or `vt,e(N)` into the register and its element
+fn split_element(s: &str) -> (&str, Option<&str>) {
+    if let Some((reg, e)) = s.split_once('[') {
+        return (reg.trim(), Some(e.strip_suffix(']').unwrap_or(e).trim()));
+    }
+    if let Some((reg, e)) = s.split_once(',') {
+        return (reg.trim(), Some(e.trim()));
+    }
+    // Aliases may contain dots, only split off what is written like an element
+    match s.rsplit_once('.') {
+        Some((reg, e)) if selector(e).is_some() || is_digits(e) => (reg, Some(e)),
+        _ => (s, None),
+    }
+}
+
+// The form of an element selector that is not a number, and its number
+fn selector(s: &str) -> Option<(Selector, &str)> {
+    if let Some(n) = s
+        .strip_prefix(['e', 'E'])
+        .and_then(|x| x.strip_prefix('('))
+        .and_then(|x| x.strip_suffix(')'))
+    {
+        let n = n.trim();
+        if is_digits(n) {
+            return Some((Selector::Lane, n));
+        }
+        return selector(n).filter(|&(x, _)| x != Selector::Lane);
+    }
+    let (selector, n) = if let Some(n) = s.strip_prefix(['e', 'E']) {
+        (Selector::Lane, n)
+    } else if let Some(n) = s.strip_suffix(['q', 'Q']) {
+        (Selector::Quarter, n)
+    } else if let Some(n) = s.strip_suffix(['h', 'H']) {
+        (Selector::Half, n)
+    } else if let Some(n) = s.strip_prefix(['q', 'Q']) {
+        (Selector::Quarter, n)
+    } else {
+        (Selector::Half, s.strip_prefix(['h', 'H'])?)
+    };
+    is_digits(n).then_some((selector, n))
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|c| c.is_ascii_digit())
+}
+
+pub fn is_label_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_' || c == '.'
+}
@@ -0,0 +1,178 @@
+use crate::ast::{self, Instruction};
+
+use alloc::{vec, vec::Vec};
+
+type I = ast::ITypeOp;
+type R = ast::RTypeOp;
+type V = ast::VTypeOp;
+type Reg = ast::Register;
+
+fn immediate(op: ast::ITypeOp, rs: Reg, rt: Reg, imm: u16) -> Instruction {
+    Instruction::Immediate {
+        op,
+        rs,
+        rt,
+        imm: ast::Immediate(imm),
+    }
+}
+
+fn register(op: ast::RTypeOp, rs: Reg, rt: Reg, rd: Reg, sa: u32) -> Instruction {
+    Instruction::Register { op, rs, rt, rd, sa }
+}
+
+/// Expands `b`, `bal` and the branches comparing against zero
+pub fn branch(op: ast::ITypeOp, rs: Reg, offset: u16) -> Instruction {
+    let (op, rs) = match op {
+        I::B => (I::Beq, Reg::Zero),
+        I::Bal => (I::Bgezal, Reg::Zero),
+        I::Beqz => (I::Beq, rs),
+        I::Bnez => (I::Bne, rs),
+        I::Beqzl => (I::Beql, rs),
+        I::Bnezl => (I::Bnel, rs),
+        op => (op, rs),
+    };
+    immediate(op, rs, Reg::Zero, offset)
+}
+
+/// Expands a branch comparing two registers into a `slt`/`sltu` into `at`
+/// followed by a branch on `at`
+///
+/// The branch is the second instruction, so `offset` is counted from its delay slot.
+#[rustfmt::skip]
+pub fn compare_branch(op: ast::ITypeOp, rs: Reg, rt: Reg, offset: u16) -> Vec<Instruction> {
+    // (set less than, swap the operands, branch if the result is set)
+    let (slt, swap, branch) = match op {
+        I::Bge => (R::Slt, false, I::Beq),
+        I::Bgt => (R::Slt, true, I::Bne),
+        I::Ble => (R::Slt, true, I::Beq),
+        I::Blt => (R::Slt, false, I::Bne),
+        I::Bgeu => (R::Sltu, false, I::Beq),
+        I::Bgtu => (R::Sltu, true, I::Bne),
+        I::Bleu => (R::Sltu, true, I::Beq),
+        I::Bltu => (R::Sltu, false, I::Bne),
+        I::Bgel => (R::Slt, false, I::Beql),
+        I::Bgtl => (R::Slt, true, I::Bnel),
+        I::Blel => (R::Slt, true, I::Beql),
+        I::Bltl => (R::Slt, false, I::Bnel),
+        I::Bgeul => (R::Sltu, false, I::Beql),
+        I::Bgtul => (R::Sltu, true, I::Bnel),
+        I::Bleul => (R::Sltu, true, I::Beql),
+        I::Bltul => (R::Sltu, false, I::Bnel),
+        _ => (R::Slt, false, op),
+    };
+    let (rs, rt) = if swap { (rt, rs) } else { (rs, rt) };
+    vec![
+        register(slt, rs, rt, Reg::At, 0),
+        immediate(branch, Reg::At, Reg::Zero, offset),
+    ]
+}
+
+/// Expands `li`, `dli` and `lli` into the shortest sequence loading `value`
+pub fn load_immediate(op: ast::ITypeOp, rt: Reg, value: i64) -> Vec<Instruction> {
+    // `li` loads a 32 bit value, so 0xFFFF8000 is the same as -0x8000
+    let value = match op {
+        I::Li => value as u32 as i32 as i64,
+        _ => value,
+    };
+    let (hi, lo) = ((value >> 16) as u16, value as u16);
+
+    if op != I::Lli && (-0x8000..=0x7FFF).contains(&value) {
+        vec![immediate(I::Addiu, Reg::Zero, rt, lo)]
+    } else if op == I::Lli || (0..=0xFFFF).contains(&value) {
+        vec![immediate(I::Ori, Reg::Zero, rt, lo)]
+    } else if (0x8000_0000..=0xFFFF_FFFF).contains(&value) {
+        // `lui` sign extends on the VR4300, so build the upper half with `ori`
+        let mut insts = vec![
+            immediate(I::Ori, Reg::Zero, rt, hi),
+            register(R::Dsll, Reg::Zero, rt, rt, 16),
+        ];
+        if lo != 0 {
+            insts.push(immediate(I::Ori, rt, rt, lo));
+        }
+        insts
+    } else if i32::try_from(value).is_err() {
+        // Load the upper word, then shift in the lower one a halfword at a time
+        let mut insts = load_immediate(I::Li, rt, value >> 32);
+        let mid = (value >> 16) as u16;
+        if mid != 0 {
+            insts.push(register(R::Dsll, Reg::Zero, rt, rt, 16));
+            insts.push(immediate(I::Ori, rt, rt, mid));
+            insts.push(register(R::Dsll, Reg::Zero, rt, rt, 16));
+        } else {
+            insts.push(register(R::Dsll32, Reg::Zero, rt, rt, 0));
+        }
+        if lo != 0 {
+            insts.push(immediate(I::Ori, rt, rt, lo));
+        }
+        insts
+    } else if lo == 0 {
+        vec![immediate(I::Lui, Reg::Zero, rt, hi)]
+    } else {
+        vec![
+            immediate(I::Lui, Reg::Zero, rt, hi),
+            immediate(I::Ori, rt, rt, lo),
+        ]
+    }
+}
+
+/// Expands `nop`, `move`, `neg`, `negu` and `not`
+pub fn register_op(op: ast::RTypeOp, rd: Reg, rs: Reg) -> Instruction {
+    match op {
+        R::Move => register(R::Addu, rs, Reg::Zero, rd, 0),
+        R::Neg => register(R::Sub, Reg::Zero, rs, rd, 0),
+        R::Negu => register(R::Subu, Reg::Zero, rs, rd, 0),
+        R::Not => register(R::Nor, rs, Reg::Zero, rd, 0),
+        _ => register(R::Sll, Reg::Zero, Reg::Zero, Reg::Zero, 0),
+    }
+}
+
+/// Expands the subtract immediate instructions into an add of `-imm`
+pub fn subtract_immediate(op: ast::ITypeOp, rs: Reg, rt: Reg, imm: i64) -> Instruction {
+    let op = match op {
+        I::Subi => I::Addi,
+        I::Subiu => I::Addiu,
+        I::Dsubi => I::Daddi,
+        I::Dsubiu => I::Daddiu,
+        op => op,
+    };
+    immediate(op, rs, rt, (-imm) as u16)
+}
+
+/// Folds an instruction written as a common idiom into its pseudoinstruction.
+///
+/// Only the forms the pseudoinstructions expand to are folded, so a folded
+/// listing assembles back to the same words.
+pub fn fold(inst: &Instruction) -> Option<Instruction> {
+    let inst = match *inst {
+        Instruction::Immediate { op, rs, rt, imm } => {
+            let op = match (op, rs, rt) {
+                (I::Beq, Reg::Zero, Reg::Zero) => I::B,
+                (I::Beq, _, Reg::Zero) => I::Beqz,
+                (I::Bne, _, Reg::Zero) => I::Bnez,
+                (I::Addiu, Reg::Zero, _) => I::Li,
+                _ => return None,
+            };
+            Instruction::Immediate { op, rs, rt, imm }
+        }
+        Instruction::Register {
+            op: R::Sll,
+            rs: Reg::Zero,
+            rt: Reg::Zero,
+            rd: Reg::Zero,
+            sa: 0,
+        } => register(R::Nop, Reg::Zero, Reg::Zero, Reg::Zero, 0),
+        Instruction::Register { op, rs, rt, rd, sa } => {
+            let op = match (op, rs, rt) {
+                (R::Addu, _, Reg::Zero) => R::Move,
+                (R::Sub, Reg::Zero, _) => R::Neg,
+                (R::Subu, Reg::Zero, _) => R::Negu,
+                (R::Nor, _, Reg::Zero) => R::Not,
+                _ => return None,
+            };
+            Instruction::Register { op, rs, rt, rd, sa }
+        }
+        Instruction::Vector { op: V::Vnop, .. } => inst.clone(),
+        _ => return None,
+    };
+    Some(inst)
+}
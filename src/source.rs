@@ -0,0 +1,719 @@
+use crate::dialect::{Dialect, Lexer};
+use crate::error::{Expansion, Line, ParserError};
+use crate::expr;
+use crate::parser::{
+    assignment, parse_string, split_labels, split_list, split_operands, split_statement,
+};
+
+use alloc::{
+    borrow::Cow,
+    collections::BTreeMap,
+    format,
+    rc::Rc,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt::Write;
+use core::panic::RefUnwindSafe;
+
+// Deepest nesting of macro expansions and includes, to stop a macro invoking
+// or a file including itself forever
+const MAX_DEPTH: usize = 64;
+
+/// Reads the files named by `.include` and `.incbin`.
+///
+/// Paths are passed as written in the input, or joined to the directory of the
+/// including file for a file included by another one. Resolvers are shared
+/// with the `Mipsasm` holding them, which stays `Send` and `Sync`.
+pub trait SourceResolver: Sync + RefUnwindSafe {
+    /// Returns the contents of the file at `path`, or why it cannot be read
+    fn resolve(&self, path: &str) -> Result<Vec<u8>, String>;
+}
+
+/// Reads included files from the filesystem.
+///
+/// # Examples
+///
+/// ```no_run
+/// use mipsasm_rsp::{FileResolver, Mipsasm};
+///
+/// let resolver = FileResolver::new("ucode");
+/// let mut mipsasm = Mipsasm::new();
+/// mipsasm.resolver(&resolver);
+/// let words = mipsasm.assemble(".include \"main.S\"");
+/// ```
+#[cfg(feature = "std")]
+#[derive(Clone, Debug)]
+pub struct FileResolver {
+    root: std::path::PathBuf,
+}
+
+#[cfg(feature = "std")]
+impl FileResolver {
+    /// Creates a resolver reading the paths in the input relative to `root`
+    pub fn new(root: impl Into<std::path::PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+}
+
+#[cfg(feature = "std")]
+impl SourceResolver for FileResolver {
+    fn resolve(&self, path: &str) -> Result<Vec<u8>, String> {
+        std::fs::read(self.root.join(path)).map_err(|e| e.to_string())
+    }
+}
+
+/// The lines of a program after expanding its macros and includes
+pub struct Source<'a> {
+    lines: Vec<SourceLine<'a>>,
+    dialect: Dialect,
+}
+
+#[derive(Clone)]
+struct SourceLine<'a> {
+    text: Cow<'a, str>,
+    // The line as written in its file, if the dialect rewrote it into `text`
+    original: Option<Cow<'a, str>>,
+    // Line number in its file
+    num: usize,
+    // Path of the included file the line is in, `None` for the input
+    file: Option<Rc<str>>,
+    // Macro invocations and includes the line is expanded from, innermost first
+    expansions: Vec<Expansion>,
+    // Contents of the file an `.incbin` names
+    binary: Option<Vec<u8>>,
+}
+
+impl<'a> SourceLine<'a> {
+    fn to_line(&self) -> Line {
+        Line::new(
+            self.num,
+            self.original.as_ref().unwrap_or(&self.text).to_string(),
+            self.file.as_ref().map(|x| x.to_string()),
+            self.expansions.clone(),
+        )
+    }
+
+    // The first `len` bytes of the line, such as the labels before a statement
+    fn prefix(&self, len: usize) -> SourceLine<'a> {
+        let text = match &self.text {
+            Cow::Borrowed(text) => Cow::Borrowed(&text[..len]),
+            Cow::Owned(text) => Cow::Owned(text[..len].to_string()),
+        };
+        SourceLine {
+            text,
+            original: self.original.clone(),
+            num: self.num,
+            file: self.file.clone(),
+            expansions: self.expansions.clone(),
+            binary: None,
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Macro<'a> {
+    params: Vec<Param>,
+    body: Vec<SourceLine<'a>>,
+    // The `.macro` line
+    line: Line,
+}
+
+#[derive(Clone)]
+struct Param {
+    name: String,
+    default: Option<String>,
+    required: bool,
+}
+
+// A macro whose body is being read, with the `.macro`s nested in it
+struct Definition<'a> {
+    name: String,
+    mac: Option<Macro<'a>>,
+    depth: usize,
+}
+
+// An `.if` block
+struct Conditional {
+    // The `.if`, `.ifdef` or `.ifndef` line
+    line: Line,
+    directive: String,
+    // Whether the lines around the block are assembled
+    outer: bool,
+    // Whether the lines after the last directive of the block are assembled
+    active: bool,
+    // Whether the lines after one of the directives of the block were
+    taken: bool,
+    after_else: bool,
+}
+
+struct Expander<'a> {
+    resolver: Option<&'a dyn SourceResolver>,
+    dialect: Dialect,
+    // Labels and constants defined so far with their value if it is known
+    // without the addresses of labels
+    symbols: BTreeMap<String, Option<i64>>,
+    macros: BTreeMap<String, Macro<'a>>,
+    definition: Option<Definition<'a>>,
+    conditionals: Vec<Conditional>,
+    // Number of expansions so far, the value of `\@`
+    count: usize,
+    lines: Vec<SourceLine<'a>>,
+    errors: Vec<ParserError>,
+}
+
+impl<'a> Source<'a> {
+    /// Splits `input` into lines, replacing every `.macro` definition with
+    /// nothing, every invocation with the macro body and every `.include` with
+    /// the lines of the file read by `resolver`. Lines in `.if` blocks are only
+    /// kept if the condition is true with the constants in `defines`. Lines
+    /// written in `dialect` are read as statements in the syntax of this crate.
+    pub fn new(
+        input: &'a str,
+        resolver: Option<&'a dyn SourceResolver>,
+        defines: &BTreeMap<String, i64>,
+        dialect: Dialect,
+    ) -> Result<Source<'a>, Vec<ParserError>> {
+        let mut expander = Expander {
+            resolver,
+            dialect,
+            symbols: defines.iter().map(|(k, v)| (k.clone(), Some(*v))).collect(),
+            macros: BTreeMap::new(),
+            definition: None,
+            conditionals: vec![],
+            count: 0,
+            lines: vec![],
+            errors: vec![],
+        };
+        let mut lexer = Lexer::new(dialect);
+        for (i, raw) in input.lines().enumerate() {
+            for text in lexer.statements(raw) {
+                let line = SourceLine {
+                    original: (text != raw).then_some(Cow::Borrowed(raw)),
+                    text,
+                    num: i + 1,
+                    file: None,
+                    expansions: vec![],
+                    binary: None,
+                };
+                expander.line(line, 0);
+            }
+        }
+        if let Some(Definition {
+            name,
+            mac: Some(mac),
+            ..
+        }) = expander.definition
+        {
+            expander.errors.push(ParserError::UnterminatedMacro {
+                line: mac.line,
+                name,
+            });
+        }
+        for Conditional {
+            line, directive, ..
+        } in expander.conditionals
+        {
+            expander
+                .errors
+                .push(ParserError::UnterminatedConditional { line, directive });
+        }
+
+        if expander.errors.is_empty() {
+            Ok(Source {
+                lines: expander.lines,
+                dialect,
+            })
+        } else {
+            Err(expander.errors)
+        }
+    }
+
+    pub fn lines(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(|x| x.text.as_ref())
+    }
+
+    /// The line with the 1-based index `num` in the expanded program
+    pub fn line(&self, num: usize) -> Line {
+        self.lines[num - 1].to_line()
+    }
+
+    /// Whether line `num` is expanded from a macro invocation
+    pub fn in_macro(&self, num: usize) -> bool {
+        self.lines[num - 1]
+            .expansions
+            .iter()
+            .any(|x| !x.is_include())
+    }
+
+    pub fn dialect(&self) -> Dialect {
+        self.dialect
+    }
+
+    /// The contents of the file included by the `.incbin` on line `num`
+    pub fn binary(&self, num: usize) -> &[u8] {
+        self.lines[num - 1].binary.as_deref().unwrap_or_default()
+    }
+}
+
+impl<'a> Expander<'a> {
+    fn line(&mut self, line: SourceLine<'a>, depth: usize) {
+        let text = line.text.as_ref();
+        let (labels, stmt) = split_labels(text);
+        let (mnemonic, ops) = split_statement(stmt);
+
+        if let Some(definition) = &mut self.definition {
+            if mnemonic.eq_ignore_ascii_case(".macro") {
+                definition.depth += 1;
+            } else if mnemonic.eq_ignore_ascii_case(".endm") {
+                if definition.depth == 0 {
+                    let definition = self.definition.take().unwrap();
+                    if let Some(mac) = definition.mac {
+                        self.macros.insert(definition.name, mac);
+                    }
+                    return;
+                }
+                definition.depth -= 1;
+            }
+            if let Some(mac) = &mut definition.mac {
+                mac.body.push(line);
+            }
+            return;
+        }
+
+        let conditional = [".if", ".ifdef", ".ifndef", ".elseif", ".else", ".endif"]
+            .iter()
+            .any(|x| mnemonic.eq_ignore_ascii_case(x));
+        if !conditional && !self.active() {
+            return;
+        }
+        self.define_symbols(&labels, stmt);
+
+        if mnemonic.eq_ignore_ascii_case(".incbin") {
+            let binary = match split_list(ops).first() {
+                Some(path) => self.read(&line, path, depth).map(|(_, data)| data),
+                None => None,
+            };
+            self.lines.push(SourceLine { binary, ..line });
+            return;
+        }
+        let define = mnemonic.eq_ignore_ascii_case(".macro");
+        let include = mnemonic.eq_ignore_ascii_case(".include");
+        let invoke = self.macros.contains_key(mnemonic) && !ops.starts_with('=');
+        if !define && !include && !invoke && !conditional {
+            self.lines.push(line);
+            return;
+        }
+        // Labels before the statement keep their line
+        if !labels.is_empty() && self.active() {
+            let len = stmt.as_ptr() as usize - text.as_ptr() as usize;
+            self.lines.push(line.prefix(len));
+        }
+        if conditional {
+            self.conditional(&line, mnemonic, ops);
+        } else if define {
+            self.define(&line, ops);
+        } else if include {
+            self.include(&line, ops, depth);
+        } else {
+            self.invoke(&line, mnemonic, ops, depth);
+        }
+    }
+
+    // Whether the lines at this point are assembled
+    fn active(&self) -> bool {
+        self.conditionals.last().is_none_or(|x| x.active)
+    }
+
+    // Records the labels and constants a line defines
+    fn define_symbols(&mut self, labels: &[&str], stmt: &str) {
+        for label in labels {
+            self.symbols.insert(label.to_string(), None);
+        }
+        let (mnemonic, ops) = split_statement(stmt);
+        let constant = match split_list(ops).as_slice() {
+            [name, value]
+                if mnemonic.eq_ignore_ascii_case(".equ")
+                    || mnemonic.eq_ignore_ascii_case(".set")
+                    || mnemonic.eq_ignore_ascii_case(".alias") =>
+            {
+                Some((*name, *value))
+            }
+            _ => assignment(stmt).map(|i| (stmt[..i].trim(), stmt[i + 1..].trim())),
+        };
+        if let Some((name, value)) = constant.filter(|(name, _)| is_identifier(name)) {
+            let value = expr::eval(value, &|name| self.value(name)).ok().flatten();
+            self.symbols.insert(name.to_string(), value);
+        }
+    }
+
+    fn value(&self, name: &str) -> Result<i64, ()> {
+        self.symbols.get(name).copied().flatten().ok_or(())
+    }
+
+    // Opens, continues or closes an `.if` block
+    fn conditional(&mut self, line: &SourceLine<'a>, mnemonic: &str, ops: &str) {
+        let directive = mnemonic.to_ascii_lowercase();
+        if directive.starts_with(".if") {
+            let outer = self.active();
+            let active = outer && self.condition(line, &directive, ops);
+            self.conditionals.push(Conditional {
+                line: line.to_line(),
+                directive: mnemonic.to_string(),
+                outer,
+                active,
+                taken: active,
+                after_else: false,
+            });
+            return;
+        }
+
+        let (outer, taken) = match self.conditionals.last() {
+            Some(block) if !block.after_else || directive == ".endif" => (block.outer, block.taken),
+            block => {
+                self.errors.push(ParserError::UnexpectedConditional {
+                    line: line.to_line(),
+                    directive: mnemonic.to_string(),
+                    after_else: block.is_some(),
+                });
+                return;
+            }
+        };
+        if directive == ".endif" {
+            self.conditionals.pop();
+            return;
+        }
+        let active = outer && !taken && (directive == ".else" || self.condition(line, ".if", ops));
+        let block = self.conditionals.last_mut().unwrap();
+        block.active = active;
+        block.taken |= active;
+        block.after_else = directive == ".else";
+    }
+
+    // Evaluates the condition of an `.if`, `.ifdef` or `.ifndef`
+    fn condition(&mut self, line: &SourceLine<'a>, directive: &str, ops: &str) -> bool {
+        let value = match directive {
+            ".if" => expr::eval(ops, &|name| self.value(name)).ok().flatten(),
+            _ if is_identifier(ops) => {
+                let defined = self.symbols.contains_key(ops);
+                Some((defined == (directive == ".ifdef")) as i64)
+            }
+            _ => {
+                self.errors.push(ParserError::InvalidLabel {
+                    line: line.to_line(),
+                    label: ops.to_string(),
+                });
+                return false;
+            }
+        };
+        match value {
+            Some(x) => x != 0,
+            None => {
+                self.errors.push(ParserError::InvalidCondition {
+                    line: line.to_line(),
+                    condition: ops.to_string(),
+                });
+                false
+            }
+        }
+    }
+
+    // Replaces `.include "path"` with the lines of the file
+    fn include(&mut self, line: &SourceLine<'a>, ops: &str, depth: usize) {
+        let path = match split_list(ops).as_slice() {
+            [path] => *path,
+            o => {
+                self.errors.push(ParserError::InvalidOperandCount {
+                    line: line.to_line(),
+                    expected: 1,
+                    found: o.len(),
+                    ops: ops.to_string(),
+                });
+                return;
+            }
+        };
+        let Some((file, data)) = self.read(line, path, depth) else {
+            return;
+        };
+        let text = match String::from_utf8(data) {
+            Ok(text) => text,
+            Err(_) => {
+                self.errors.push(ParserError::InvalidInclude {
+                    line: line.to_line(),
+                    path: path.to_string(),
+                    reason: "the file is not valid UTF-8".to_string(),
+                });
+                return;
+            }
+        };
+
+        let file: Rc<str> = file.into();
+        let mut expansions = vec![Expansion::new(
+            unquote(path).to_string(),
+            true,
+            &line.to_line(),
+        )];
+        expansions.extend(line.expansions.iter().cloned());
+        let mut lexer = Lexer::new(self.dialect);
+        for (i, raw) in text.lines().enumerate() {
+            for text in lexer.statements(raw) {
+                let line = SourceLine {
+                    original: (text != raw).then(|| Cow::Owned(raw.to_string())),
+                    text: Cow::Owned(text.into_owned()),
+                    num: i + 1,
+                    file: Some(file.clone()),
+                    expansions: expansions.clone(),
+                    binary: None,
+                };
+                self.line(line, depth + 1);
+            }
+        }
+    }
+
+    // Reads the file named by the string `path`, returning its path relative
+    // to the input and its contents
+    fn read(&mut self, line: &SourceLine, path: &str, depth: usize) -> Option<(String, Vec<u8>)> {
+        let reason = match parse_string(path).map(String::from_utf8) {
+            Some(Ok(path)) => {
+                let path = join(line.file.as_deref(), &path);
+                match self.resolver {
+                    _ if depth == MAX_DEPTH => {
+                        format!("files are included more than {} levels deep", MAX_DEPTH)
+                    }
+                    Some(resolver) => match resolver.resolve(&path) {
+                        Ok(data) => return Some((path, data)),
+                        Err(reason) => reason,
+                    },
+                    None => "no source resolver is set".to_string(),
+                }
+            }
+            _ => {
+                self.errors.push(ParserError::InvalidString {
+                    line: line.to_line(),
+                    string: path.to_string(),
+                });
+                return None;
+            }
+        };
+        // The quoted path, as the file name can also be part of the directive
+        self.errors.push(ParserError::InvalidInclude {
+            line: line.to_line(),
+            path: path.to_string(),
+            reason,
+        });
+        None
+    }
+
+    // Starts reading the body of the macro defined by `.macro name params`
+    fn define(&mut self, line: &SourceLine<'a>, ops: &str) {
+        let (name, params) = match ops.find(|c: char| c.is_whitespace() || c == ',') {
+            Some(i) => (&ops[..i], ops[i..].trim_start().trim_start_matches(',')),
+            None => (ops, ""),
+        };
+        let mut mac = Some(Macro {
+            params: vec![],
+            body: vec![],
+            line: line.to_line(),
+        });
+        if !is_identifier(name) {
+            self.errors.push(ParserError::InvalidMacroName {
+                line: line.to_line(),
+                name: name.to_string(),
+            });
+            mac = None;
+        } else if let Some(first) = self.macros.get(name) {
+            self.errors.push(ParserError::MultipleMacroDefinition {
+                line: line.to_line(),
+                name: name.to_string(),
+                first: first.line.clone(),
+            });
+            mac = None;
+        }
+
+        for param in split_operands(params) {
+            let (param_name, default) = match param.split_once('=') {
+                Some((param_name, default)) => (param_name.trim(), Some(default.trim())),
+                None => (param, None),
+            };
+            let (param_name, required) = match param_name.strip_suffix(":req") {
+                Some(param_name) => (param_name, true),
+                None => (param_name, false),
+            };
+            let duplicate = mac
+                .as_ref()
+                .is_some_and(|x| x.params.iter().any(|x| x.name == param_name));
+            if !is_identifier(param_name) || duplicate {
+                self.errors.push(ParserError::InvalidMacroParameter {
+                    line: line.to_line(),
+                    param: param.to_string(),
+                });
+                mac = None;
+            }
+            if let Some(mac) = &mut mac {
+                mac.params.push(Param {
+                    name: param_name.to_string(),
+                    default: default.map(|x| x.to_string()),
+                    required,
+                });
+            }
+        }
+
+        self.definition = Some(Definition {
+            name: name.to_string(),
+            mac,
+            depth: 0,
+        });
+    }
+
+    // Replaces `name args` with the body of the macro
+    fn invoke(&mut self, line: &SourceLine<'a>, name: &str, ops: &str, depth: usize) {
+        if depth == MAX_DEPTH {
+            self.errors.push(ParserError::MacroRecursion {
+                line: line.to_line(),
+                name: name.to_string(),
+                depth,
+            });
+            return;
+        }
+        let mac = self.macros[name].clone();
+
+        // Arguments are passed in order or as `param=value`
+        let mut values = vec![None; mac.params.len()];
+        let mut next = 0;
+        for arg in split_list(ops) {
+            let named = arg.split_once('=').and_then(|(param, value)| {
+                let index = mac.params.iter().position(|x| x.name == param.trim())?;
+                Some((index, value.trim()))
+            });
+            let (index, value) = match named {
+                Some(named) => named,
+                None if next < mac.params.len() => {
+                    next += 1;
+                    (next - 1, arg)
+                }
+                None => {
+                    self.errors.push(ParserError::InvalidMacroArgument {
+                        line: line.to_line(),
+                        name: name.to_string(),
+                        arg: arg.to_string(),
+                    });
+                    return;
+                }
+            };
+            if !value.is_empty() {
+                values[index] = Some(value);
+            }
+        }
+
+        let mut args = vec![];
+        for (param, value) in mac.params.iter().zip(values) {
+            let value = match (value, &param.default) {
+                (Some(value), _) => value,
+                (None, Some(default)) => default,
+                (None, None) if param.required => {
+                    self.errors.push(ParserError::MissingMacroArgument {
+                        line: line.to_line(),
+                        name: name.to_string(),
+                        param: param.name.clone(),
+                    });
+                    return;
+                }
+                (None, None) => "",
+            };
+            args.push((param.name.as_str(), value));
+        }
+
+        let count = self.count;
+        self.count += 1;
+        let mut expansions = vec![Expansion::new(name.to_string(), false, &line.to_line())];
+        expansions.extend(line.expansions.iter().cloned());
+        let dialect = self.dialect;
+        let expand = |text: &Cow<'a, str>| match dialect {
+            Dialect::Armips => Cow::Owned(substitute_bare(text, &args)),
+            _ if text.contains('\\') => Cow::Owned(substitute(text, &args, count)),
+            _ => text.clone(),
+        };
+        for body in &mac.body {
+            let body = SourceLine {
+                text: expand(&body.text),
+                original: body.original.as_ref().map(expand),
+                num: body.num,
+                file: body.file.clone(),
+                expansions: expansions.clone(),
+                binary: None,
+            };
+            self.line(body, depth + 1);
+        }
+    }
+}
+
+// Replaces `\param` with its argument and `\@` with the number of the
+// expansion, `\()` separates a parameter from the text after it
+fn substitute(text: &str, args: &[(&str, &str)], count: usize) -> String {
+    let mut s = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find('\\') {
+        s.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        if let Some(after) = rest.strip_prefix('@') {
+            write!(s, "{}", count).unwrap();
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix("()") {
+            rest = after;
+        } else {
+            let len = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            match args.iter().find(|(name, _)| *name == &rest[..len]) {
+                Some((_, value)) => {
+                    s.push_str(value);
+                    rest = &rest[len..];
+                }
+                None => s.push('\\'),
+            }
+        }
+    }
+    s.push_str(rest);
+    s
+}
+
+// Replaces the parameters of an armips macro, which are used without `\`
+fn substitute_bare(text: &str, args: &[(&str, &str)]) -> String {
+    let mut s = String::new();
+    let mut rest = text;
+    while let Some(i) = rest.find(|c: char| c.is_ascii_alphanumeric() || c == '_') {
+        s.push_str(&rest[..i]);
+        rest = &rest[i..];
+        let len = rest
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(rest.len());
+        match args.iter().find(|(name, _)| *name == &rest[..len]) {
+            Some((_, value)) => s.push_str(value),
+            None => s.push_str(&rest[..len]),
+        }
+        rest = &rest[len..];
+    }
+    s.push_str(rest);
+    s
+}
+
+// Joins `path` to the directory of the file `from` including it
+fn join(from: Option<&str>, path: &str) -> String {
+    match from.and_then(|x| x.rfind(['/', '\\'])) {
+        Some(i) if !path.starts_with(['/', '\\']) => format!("{}{}", &from.unwrap()[..=i], path),
+        _ => path.to_string(),
+    }
+}
+
+// A string literal without its quotes
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"')
+        .and_then(|x| x.strip_suffix('"'))
+        .unwrap_or(s)
+}
+
+fn is_identifier(s: &str) -> bool {
+    s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
@@ -0,0 +1,141 @@
+use crate::ast::{self, Instruction};
+
+use core::fmt;
+
+type I = ast::ITypeOp;
+type R = ast::RTypeOp;
+
+/// The processor a program is assembled for or disassembled from
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// The Reality Signal Processor, a 32 bit scalar core with the vector unit as COP2
+    Rsp,
+    /// The VR4300 main CPU, a 64 bit core with a FPU and no vector unit
+    Vr4300,
+}
+
+impl fmt::Display for Target {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Target::Rsp => write!(f, "RSP"),
+            Target::Vr4300 => write!(f, "VR4300"),
+        }
+    }
+}
+
+impl Target {
+    /// Returns whether the processor can execute `inst`
+    pub fn supports(&self, inst: &Instruction) -> bool {
+        match self {
+            Target::Rsp => match inst {
+                Instruction::Immediate { op, .. } => rsp_immediate(op),
+                Instruction::Register { op, .. } => rsp_register(op),
+                Instruction::Jump { .. }
+                | Instruction::Vector { .. }
+                | Instruction::VectorMemory { .. } => true,
+            },
+            Target::Vr4300 => match inst {
+                Instruction::Register { op, .. } => {
+                    !matches!(op, R::Cfc2 | R::Ctc2 | R::Mfc2 | R::Mtc2)
+                }
+                Instruction::Vector { .. } | Instruction::VectorMemory { .. } => false,
+                Instruction::Immediate { .. } | Instruction::Jump { .. } => true,
+            },
+        }
+    }
+}
+
+// The RSP has no multiply/divide unit, FPU, TLB, 64 bit operations, traps,
+// branch likely or unaligned loads and stores
+fn rsp_immediate(op: &ast::ITypeOp) -> bool {
+    matches!(
+        op,
+        I::Addi
+            | I::Addiu
+            | I::Andi
+            | I::Beq
+            | I::Bgez
+            | I::Bgezal
+            | I::Bgtz
+            | I::Blez
+            | I::Bltz
+            | I::Bltzal
+            | I::Bne
+            | I::Lb
+            | I::Lbu
+            | I::Lh
+            | I::Lhu
+            | I::Lui
+            | I::Lw
+            | I::Lwu
+            | I::Ori
+            | I::Sb
+            | I::Sh
+            | I::Slti
+            | I::Sltiu
+            | I::Sw
+            | I::Xori
+            | I::B
+            | I::Bal
+            | I::Beqz
+            | I::Bnez
+            | I::Bge
+            | I::Bgt
+            | I::Ble
+            | I::Blt
+            | I::Bgeu
+            | I::Bgtu
+            | I::Bleu
+            | I::Bltu
+            | I::Lli
+            | I::Li
+            | I::Subi
+            | I::Subiu
+    )
+}
+
+fn rsp_register(op: &ast::RTypeOp) -> bool {
+    matches!(
+        op,
+        R::Add
+            | R::Addu
+            | R::And
+            | R::Break
+            | R::Cfc2
+            | R::Ctc2
+            | R::Jalr
+            | R::Jr
+            | R::Mfc0
+            | R::Mfc2
+            | R::Mtc0
+            | R::Mtc2
+            | R::Nor
+            | R::Or
+            | R::Sll
+            | R::Sllv
+            | R::Slt
+            | R::Sltu
+            | R::Sra
+            | R::Srav
+            | R::Srl
+            | R::Srlv
+            | R::Sub
+            | R::Subu
+            | R::Xor
+            | R::Abs
+            | R::Clear
+            | R::Move
+            | R::Neg
+            | R::Negu
+            | R::Nop
+            | R::Not
+            | R::Seq
+            | R::Sge
+            | R::Sgeu
+            | R::Sgt
+            | R::Sgtu
+            | R::Sle
+            | R::Sleu
+            | R::Sne
+    )
+}